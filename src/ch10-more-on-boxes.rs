@@ -18,4 +18,320 @@ y += 2;
 
 let x = box 5i; // immutable
 let mut y = box 5i; // mutable
-*y += 2; // the `*` operator is needed to access the contained value
\ No newline at end of file
+*y += 2; // the `*` operator is needed to access the contained value
+
+/*Boxes are what make a tree of nodes possible: a node owns its children through boxes. An R-tree is such a 
+tree, each node keeping the bounding box of everything below it, so that a query only walks down the 
+branches whose boxes it touches instead of looking at every shape of a scene. Aabb, Point and the 
+Bounded trait are the ones from the generics chapter, Metric the one from the references chapter.*/
+
+use std::cmp::{Ordering, Equal};
+use std::f64::{INFINITY, NEG_INFINITY};
+
+static NODE_CAPACITY: uint = 16;
+
+enum Node<T> {
+    Leaf(Vec<(Aabb, T)>),
+    Branch(Vec<(Aabb, Box<Node<T>>)>)
+}
+
+struct RTree<T> {
+    root: Option<Box<Node<T>>>
+}
+
+impl<T: Bounded> RTree<T> {
+    /* Sort-Tile-Recursive bulk loading: the entries are sorted along x, cut into vertical slices, each slice
+    is sorted along y and cut into nodes. The nodes are then packed the same way, level after level,
+    until a single one is left. */
+    fn bulk_load(items: Vec<T>) -> RTree<T> {
+        let entries: Vec<(Aabb, T)> = items.move_iter().map(|item| (item.bounds(), item)).collect();
+        let mut level: Vec<(Aabb, Box<Node<T>>)> = str_groups(entries).move_iter()
+            .map(|group| (group_bounds(&group), box Leaf(group)))
+            .collect();
+        while level.len() > 1 {
+            level = str_groups(level).move_iter()
+                .map(|group| (group_bounds(&group), box Branch(group)))
+                .collect();
+        }
+        RTree { root: level.pop().map(|(_, node)| node) }
+    }
+}
+
+impl<T> RTree<T> {
+    // Every item whose bounds touch `window`
+    fn search<'a>(&'a self, window: &Aabb) -> Vec<&'a T> {
+        let mut found = Vec::new();
+        match self.root {
+            Some(ref root) => search_node(&**root, window, &mut found),
+            None => ()
+        }
+        found
+    }
+
+    /* The item closest to `p` under `metric`. `distance` tells the exact distance from a point to an item, measured
+    with the metric it is handed, which is the same one that prunes the search with the bounds of the items. */
+    fn nearest<'a>(&'a self, p: &Point<f64>, metric: &Metric,
+                   mut distance: |&T, &Point<f64>, &Metric| -> f64) -> Option<&'a T> {
+        let mut best = None;
+        let mut best_distance = INFINITY;
+        match self.root {
+            Some(ref root) => nearest_node(&**root, p, metric, &mut distance, &mut best, &mut best_distance),
+            None => ()
+        }
+        best
+    }
+}
+
+fn search_node<'a, T>(node: &'a Node<T>, window: &Aabb, found: &mut Vec<&'a T>) {
+    match *node {
+        Leaf(ref entries) => {
+            for &(ref bounds, ref item) in entries.iter() {
+                if overlaps(bounds, window) { found.push(item); }
+            }
+        },
+        Branch(ref children) => {
+            for &(ref bounds, ref child) in children.iter() {
+                if overlaps(bounds, window) { search_node(&**child, window, found); }
+            }
+        }
+    }
+}
+
+fn nearest_node<'a, T>(node: &'a Node<T>, p: &Point<f64>, metric: &Metric,
+                       distance: &mut |&T, &Point<f64>, &Metric| -> f64,
+                       best: &mut Option<&'a T>, best_distance: &mut f64) {
+    match *node {
+        Leaf(ref entries) => {
+            for &(ref bounds, ref item) in entries.iter() {
+                if metric.box_distance(p, &bounds.min, &bounds.max) < *best_distance {
+                    let d = (*distance)(item, p, metric);
+                    if d < *best_distance {
+                        *best_distance = d;
+                        *best = Some(item);
+                    }
+                }
+            }
+        },
+        Branch(ref children) => {
+            // Closest boxes first, they are the most likely to lower `best_distance` early on
+            let mut order: Vec<(f64, &Box<Node<T>>)> = children.iter()
+                .map(|&(ref bounds, ref child)| (metric.box_distance(p, &bounds.min, &bounds.max), child))
+                .collect();
+            order.sort_by(|&(a, _), &(b, _)| compare(a, b));
+            for &(d, child) in order.iter() {
+                if d >= *best_distance { break; }
+                nearest_node(&**child, p, metric, distance, best, best_distance);
+            }
+        }
+    }
+}
+
+fn str_groups<X>(mut entries: Vec<(Aabb, X)>) -> Vec<Vec<(Aabb, X)>> {
+    let node_count = (entries.len() + NODE_CAPACITY - 1) / NODE_CAPACITY;
+    let slice_count = (node_count as f64).sqrt().ceil() as uint;
+    entries.sort_by(|&(ref a, _), &(ref b, _)| compare(a.min.x + a.max.x, b.min.x + b.max.x));
+    let mut groups = Vec::new();
+    for mut slice in chunk(entries, slice_count * NODE_CAPACITY).move_iter() {
+        slice.sort_by(|&(ref a, _), &(ref b, _)| compare(a.min.y + a.max.y, b.min.y + b.max.y));
+        groups.push_all_move(chunk(slice, NODE_CAPACITY));
+    }
+    groups
+}
+
+// Like the chunks method of slices, except that the items are moved into the chunks
+fn chunk<X>(items: Vec<X>, size: uint) -> Vec<Vec<X>> {
+    let mut chunks = Vec::new();
+    let mut current = Vec::new();
+    for item in items.move_iter() {
+        current.push(item);
+        if current.len() == size {
+            chunks.push(current);
+            current = Vec::new();
+        }
+    }
+    if !current.is_empty() { chunks.push(current); }
+    chunks
+}
+
+fn group_bounds<X>(group: &Vec<(Aabb, X)>) -> Aabb {
+    group.iter().fold(Aabb::empty(), |bounds, &(ref b, _)| bounds.union(b))
+}
+
+fn overlaps(a: &Aabb, b: &Aabb) -> bool {
+    a.min.x <= b.max.x && b.min.x <= a.max.x &&
+    a.min.y <= b.max.y && b.min.y <= a.max.y
+}
+
+fn compare(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b).unwrap_or(Equal)
+}
+
+/*To index a scene of trait objects, the objects have to be both drawable and bounded. A trait object can 
+only name one trait, so both are gathered under a new one that every drawable and bounded type gets for free.*/
+
+trait SceneShape : Drawable + Bounded {}
+impl<T: Drawable + Bounded> SceneShape for T {}
+
+impl Bounded for Box<SceneShape> {
+    fn bounds(&self) -> Aabb { (**self).bounds() }
+}
+
+let scene: Vec<Box<SceneShape>> = vec![box new_circle() as Box<SceneShape>, box new_rectangle() as Box<SceneShape>];
+let index = RTree::bulk_load(scene);
+
+// Only draw what is in view
+let view = Aabb { min: Point { x: 0.0, y: 0.0 }, max: Point { x: 800.0, y: 600.0 } };
+let mut svg = Svg::new(800.0, 600.0);
+for shape in index.search(&view).iter() {
+    shape.draw(&mut svg);
+}
+
+// Picking the shape under the cursor
+let cursor = Point { x: 120.0, y: 45.0 };
+let picked = index.nearest(&cursor, &Euclidean, |shape, p, metric| {
+    let bounds = shape.bounds();
+    metric.box_distance(p, &bounds.min, &bounds.max)
+});
+
+
+/*A k-d tree is another tree of boxes, for points this time. Each node holds a point and splits the plane in two 
+along x or along y, alternating with depth: the points on the lower side go to the left subtree, the others to the 
+right one. A search goes down the side of the query point first, and only visits the other side if the splitting line 
+is closer than the best points found so far. The distance is given as a Metric, from the references chapter.*/
+
+struct KdNode {
+    point: Point<f64>,
+    left: Option<Box<KdNode>>,
+    right: Option<Box<KdNode>>
+}
+
+struct KdTree {
+    root: Option<Box<KdNode>>,
+    size: uint
+}
+
+impl KdTree {
+    fn new() -> KdTree { KdTree { root: None, size: 0 } }
+
+    // Splitting on medians gives a balanced tree, better than inserting the points one by one
+    fn from_points(points: Vec<Point<f64>>) -> KdTree {
+        let size = points.len();
+        KdTree { root: build(points, 0), size: size }
+    }
+
+    // Insertions do not rebalance, rebuild with from_points after many of them
+    fn insert(&mut self, p: Point<f64>) {
+        insert_node(&mut self.root, p, 0);
+        self.size += 1;
+    }
+
+    fn len(&self) -> uint { self.size }
+
+    fn is_empty(&self) -> bool { self.size == 0 }
+
+    // The k points closest to `p`, closest first
+    fn nearest<'a>(&'a self, p: &Point<f64>, k: uint, metric: &Metric) -> Vec<&'a Point<f64>> {
+        let mut best = Vec::new();
+        if k > 0 {
+            nearest_in(&self.root, p, k, metric, 0, &mut best);
+        }
+        best.move_iter().map(|(_, point)| point).collect()
+    }
+
+    // Every point within `radius` of `p`, in no particular order
+    fn within<'a>(&'a self, p: &Point<f64>, radius: f64, metric: &Metric) -> Vec<&'a Point<f64>> {
+        let mut found = Vec::new();
+        within_in(&self.root, p, radius, metric, 0, &mut found);
+        found
+    }
+}
+
+fn coordinate(p: &Point<f64>, depth: uint) -> f64 {
+    if depth % 2 == 0 { p.x } else { p.y }
+}
+
+fn build(points: Vec<Point<f64>>, depth: uint) -> Option<Box<KdNode>> {
+    if points.is_empty() {
+        return None;
+    }
+    let mut points = points;
+    points.sort_by(|a, b| compare(coordinate(a, depth), coordinate(b, depth)));
+    let median = points.len() / 2;
+    let (mut lower, mut upper) = (Vec::new(), Vec::new());
+    let mut split = None;
+    for (i, p) in points.move_iter().enumerate() {
+        if i < median { lower.push(p) } else if i == median { split = Some(p) } else { upper.push(p) }
+    }
+    Some(box KdNode { point: split.unwrap(), left: build(lower, depth + 1), right: build(upper, depth + 1) })
+}
+
+fn insert_node(node: &mut Option<Box<KdNode>>, p: Point<f64>, depth: uint) {
+    match *node {
+        Some(ref mut n) => {
+            if coordinate(&p, depth) < coordinate(&n.point, depth) {
+                insert_node(&mut n.left, p, depth + 1);
+            } else {
+                insert_node(&mut n.right, p, depth + 1);
+            }
+            return;
+        },
+        None => ()
+    }
+    *node = Some(box KdNode { point: p, left: None, right: None });
+}
+
+// Lower bound of the distance from `p` to the half-plane on the far side of the split
+fn far_side_distance(p: &Point<f64>, split: f64, depth: uint, far_is_upper: bool, metric: &Metric) -> f64 {
+    let (mut corner, mut opposite) = (Point { x: NEG_INFINITY, y: NEG_INFINITY }, Point { x: INFINITY, y: INFINITY });
+    match (depth % 2 == 0, far_is_upper) {
+        (true, true) => corner.x = split,
+        (true, false) => opposite.x = split,
+        (false, true) => corner.y = split,
+        (false, false) => opposite.y = split
+    }
+    metric.box_distance(p, &corner, &opposite)
+}
+
+fn nearest_in<'a>(node: &'a Option<Box<KdNode>>, p: &Point<f64>, k: uint, metric: &Metric, depth: uint,
+                  best: &mut Vec<(f64, &'a Point<f64>)>) {
+    let n = match *node { Some(ref n) => n, None => return };
+
+    let d = metric.distance(p, &n.point);
+    if best.len() < k || d < best[best.len() - 1].val0() {
+        let at = best.iter().position(|&(other, _)| other > d).unwrap_or(best.len());
+        best.insert(at, (d, &n.point));
+        best.truncate(k);
+    }
+
+    let split = coordinate(&n.point, depth);
+    let far_is_upper = coordinate(p, depth) < split;
+    let (near, far) = if far_is_upper { (&n.left, &n.right) } else { (&n.right, &n.left) };
+    nearest_in(near, p, k, metric, depth + 1, best);
+    if best.len() < k || far_side_distance(p, split, depth, far_is_upper, metric) < best[best.len() - 1].val0() {
+        nearest_in(far, p, k, metric, depth + 1, best);
+    }
+}
+
+fn within_in<'a>(node: &'a Option<Box<KdNode>>, p: &Point<f64>, radius: f64, metric: &Metric, depth: uint,
+                 found: &mut Vec<&'a Point<f64>>) {
+    let n = match *node { Some(ref n) => n, None => return };
+
+    if metric.distance(p, &n.point) <= radius {
+        found.push(&n.point);
+    }
+
+    let split = coordinate(&n.point, depth);
+    let far_is_upper = coordinate(p, depth) < split;
+    let (near, far) = if far_is_upper { (&n.left, &n.right) } else { (&n.right, &n.left) };
+    within_in(near, p, radius, metric, depth + 1, found);
+    if far_side_distance(p, split, depth, far_is_upper, metric) <= radius {
+        within_in(far, p, radius, metric, depth + 1, found);
+    }
+}
+
+let mut samples = KdTree::from_points(vec![Point { x: 0.0, y: 0.0 }, Point { x: 3.0, y: 1.0 },
+                                           Point { x: -2.0, y: 4.0 }, Point { x: 5.0, y: -1.0 }]);
+samples.insert(Point { x: 1.0, y: 1.0 });
+assert!(samples.len() == 5);
+let snapped = samples.nearest(&cursor, 1, &Euclidean);
+let around = samples.within(&cursor, 2.0, &Manhattan);
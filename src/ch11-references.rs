@@ -2,22 +2,28 @@
  *references never imply ownership - they are "borrowed". You can borrow a reference to any object, 
  *and the compiler verifies that it cannot outlive the lifetime of the object.*/
 
-struct Point {
-    x: f64,
-    y: f64
+// Point<T> and the Coordinate trait are the ones from the data structures chapter
+struct Point<T> {
+    x: T,
+    y: T
 }
 
 // Differente way to allocate a point at different location
-let on_the_stack :     Point  =     Point { x: 3.0, y: 4.0 };
-let on_the_heap  : Box<Point> = box Point { x: 7.0, y: 9.0 };
+let on_the_stack :     Point<f64>  =     Point { x: 3.0, y: 4.0 };
+let on_the_heap  : Box<Point<f64>> = box Point { x: 7.0, y: 9.0 };
 
 // Let's say we want to create a function that computes the distance between two points
 // In order to avoid unecessay copy, we'll pass point using ref
 
-fn compute_distance(p1: &Point, p2: &Point) -> f64 {
+fn compute_distance<T: Coordinate>(p1: &Point<T>, p2: &Point<T>) -> f64 {
+    squared_distance(p1, p2).to_f64().sqrt()
+}
+
+// The square root is what makes a distance inexact, its square stays exact on integer grids
+fn squared_distance<T: Coordinate>(p1: &Point<T>, p2: &Point<T>) -> T {
     let x_d = p1.x - p2.x;
     let y_d = p1.y - p2.y;
-    (x_d * x_d + y_d * y_d).sqrt()
+    x_d * x_d + y_d * y_d
 }
 
 // We can call this function on our previously defined points
@@ -32,6 +38,381 @@ compute_distance(&on_the_stack, &*on_the_heap);
  *Likewise, in the case of owned_box, the & operator is used in conjunction with 
  *the * operator to take a reference to the contents of the box.*/
 
+/*Euclidean distance is not the only one around: a grid is better measured in moves along the axes,
+and the distance between two places on Earth follows the surface of the sphere. A Metric trait lets
+spatial queries take the distance to use as a parameter, each metric being a struct of its own.*/
+
+trait Metric {
+    fn distance(&self, p1: &Point<f64>, p2: &Point<f64>) -> f64;
+
+    /* Lower bound of the distance from `p` to the points of the box between `corner` (minimum
+    coordinates) and `opposite` (maximum coordinates), which spatial indexes use to skip whole
+    branches. Clamping `p` into the box gives the closest point for any metric built on
+    coordinate differences. */
+    fn box_distance(&self, p: &Point<f64>, corner: &Point<f64>, opposite: &Point<f64>) -> f64 {
+        let closest = Point { x: p.x.max(corner.x).min(opposite.x), y: p.y.max(corner.y).min(opposite.y) };
+        self.distance(p, &closest)
+    }
+}
+
+struct Euclidean;
+struct SquaredEuclidean;  // same ordering as Euclidean, without the square root
+struct Manhattan;         // moves along the axes only
+struct Chebyshev;         // diagonal moves cost as much as straight ones
+
+// Great-circle distance, with longitudes in x and latitudes in y, in degrees
+struct Haversine {
+    radius: f64  // 6371.0 for kilometers on Earth
+}
+
+impl Metric for Euclidean {
+    fn distance(&self, p1: &Point<f64>, p2: &Point<f64>) -> f64 { compute_distance(p1, p2) }
+}
+
+impl Metric for SquaredEuclidean {
+    fn distance(&self, p1: &Point<f64>, p2: &Point<f64>) -> f64 { squared_distance(p1, p2) }
+}
+
+impl Metric for Manhattan {
+    fn distance(&self, p1: &Point<f64>, p2: &Point<f64>) -> f64 { (p1.x - p2.x).abs() + (p1.y - p2.y).abs() }
+}
+
+impl Metric for Chebyshev {
+    fn distance(&self, p1: &Point<f64>, p2: &Point<f64>) -> f64 { (p1.x - p2.x).abs().max((p1.y - p2.y).abs()) }
+}
+
+impl Metric for Haversine {
+    fn distance(&self, p1: &Point<f64>, p2: &Point<f64>) -> f64 {
+        let (lat1, lat2) = (p1.y.to_radians(), p2.y.to_radians());
+        let (d_lat, d_lon) = ((p2.y - p1.y).to_radians(), (p2.x - p1.x).to_radians());
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * self.radius * a.sqrt().min(1.0).asin()
+    }
+
+    // Clamping longitudes does not give the closest point on a sphere, but no change of
+    // longitude brings two points closer than their difference in latitude
+    fn box_distance(&self, p: &Point<f64>, corner: &Point<f64>, opposite: &Point<f64>) -> f64 {
+        let latitude = p.y.max(corner.y).min(opposite.y);
+        self.radius * (p.y - latitude).abs().to_radians()
+    }
+}
+
+let paris = Point { x: 2.35, y: 48.86 };
+let london = Point { x: -0.13, y: 51.51 };
+println!("{} km", Haversine { radius: 6371.0 }.distance(&paris, &london));
+println!("{} moves", Manhattan.distance(&on_the_stack, &*on_the_heap));
+
+// The same function in three dimensions, for points that also have a z coordinate
+
+struct Point3<T> {
+    x: T,
+    y: T,
+    z: T
+}
+
+fn compute_distance3<T: Coordinate>(p1: &Point3<T>, p2: &Point3<T>) -> f64 {
+    squared_distance3(p1, p2).to_f64().sqrt()
+}
+
+fn squared_distance3<T: Coordinate>(p1: &Point3<T>, p2: &Point3<T>) -> T {
+    let x_d = p1.x - p2.x;
+    let y_d = p1.y - p2.y;
+    let z_d = p1.z - p2.z;
+    x_d * x_d + y_d * y_d + z_d * z_d
+}
+
+compute_distance3(&Point3 { x: 0.0, y: 0.0, z: 0.0 }, &Point3 { x: 1.0, y: 2.0, z: 2.0 }); // 3
+
+// The same goes for segments, a tuple struct holding both ends
+
+struct Segment(Point<f64>, Point<f64>);
+
+// The point of the segment closest to `p`, the projection of `p` clamped to the ends
+fn closest_point(s: &Segment, p: &Point<f64>) -> Point<f64> {
+    let &Segment(a, b) = s;
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length = dx * dx + dy * dy;
+    if length == 0.0 {
+        return a;
+    }
+    let t = (((p.x - a.x) * dx + (p.y - a.y) * dy) / length).max(0.0).min(1.0);
+    Point { x: a.x + t * dx, y: a.y + t * dy }
+}
+
+fn segment_distance(s: &Segment, p: &Point<f64>) -> f64 {
+    compute_distance(&closest_point(s, p), p)
+}
+
+enum SegmentIntersection {
+    Disjoint,
+    Crossing(Point<f64>),
+    Overlap(Segment)  // collinear segments sharing more than a point
+}
+
+/*Whether the segments meet is only decided from the signs of orientation, the cross product from the data
+structures chapter. These signs never contradict each other, so touching ends and degenerate segments reduced
+to a point are handled like any other case. Only the coordinates of the resulting point are subject to rounding.*/
+fn intersect_segments(s1: &Segment, s2: &Segment) -> SegmentIntersection {
+    let (&Segment(a, b), &Segment(c, d)) = (s1, s2);
+    let (d1, d2) = (orientation(c, d, a), orientation(c, d, b));
+    let (d3, d4) = (orientation(a, b, c), orientation(a, b, d));
+
+    if d1 == 0.0 && d2 == 0.0 && d3 == 0.0 && d4 == 0.0 {
+        // All on the same line: compare the positions of the ends along it
+        let (dx, dy) = if a.x != b.x || a.y != b.y { (b.x - a.x, b.y - a.y) } else { (d.x - c.x, d.y - c.y) };
+        let length = dx * dx + dy * dy;
+        if length == 0.0 {
+            // Two single points
+            return if a.x == c.x && a.y == c.y { Crossing(a) } else { Disjoint };
+        }
+        let position = |p: &Point<f64>| ((p.x - a.x) * dx + (p.y - a.y) * dy) / length;
+        let at = |t: f64| Point { x: a.x + t * dx, y: a.y + t * dy };
+        let (t1, t2, t3, t4) = (position(&a), position(&b), position(&c), position(&d));
+        let lo = t1.min(t2).max(t3.min(t4));
+        let hi = t1.max(t2).min(t3.max(t4));
+        return if lo > hi { Disjoint } else if lo == hi { Crossing(at(lo)) } else { Overlap(Segment(at(lo), at(hi))) };
+    }
+
+    if d1 * d2 <= 0.0 && d3 * d4 <= 0.0 {
+        // `a` and `b` are on both sides of (c, d), the distances to it give where the crossing is
+        let t = d1 / (d1 - d2);
+        Crossing(Point { x: a.x + t * (b.x - a.x), y: a.y + t * (b.y - a.y) })
+    } else {
+        Disjoint
+    }
+}
+
+let wire = Segment(Point { x: 0.0, y: 0.0 }, Point { x: 10.0, y: 0.0 });
+let other = Segment(Point { x: 5.0, y: -5.0 }, Point { x: 5.0, y: 5.0 });
+match intersect_segments(&wire, &other) {
+    Crossing(p) => println!("crossing at {} {}", p.x, p.y),
+    Overlap(_) => println!("overlapping"),
+    Disjoint => println!("apart")
+}
+println!("{}", segment_distance(&wire, &on_the_stack));
+
+/*Testing every pair of segments for an intersection takes quadratic time. The Bentley-Ottmann sweep moves a
+vertical line from left to right, stopping at the ends of the segments and at the crossings found so far. Along the
+way it keeps the segments cut by the line sorted from bottom to top: two segments can only cross after being next to
+each other in that order, so only neighbours are ever tested. The sorted segments are kept in a treap, a binary tree
+balanced by random priorities. At each stop it is split into the segments below the stop, those going through it and
+those above, then merged back with the middle part reordered, all in O(log n) expected time. That gives
+O((n + k) log n) for n segments and k intersecting pairs.
+
+Crossing points are rounded, so a segment goes through a stop when it passes within a tolerance of it. The tolerance
+grows with the size of the coordinates: the rounding error of a crossing is relative to them.
+
+The result is the list of intersecting pairs, as indices in the given slice.*/
+
+use std::cmp::{Ordering, Equal, Greater};
+use std::collections::{PriorityQueue, HashSet};
+use std::f64::INFINITY;
+use std::rand::{task_rng, Rng};
+
+// Relative to the largest coordinate of the input
+static EPSILON: f64 = 1e-9;
+
+struct Event {
+    point: Point<f64>,
+    start: Option<uint>  // the segment starting there, if any
+}
+
+impl PartialEq for Event {
+    fn eq(&self, other: &Event) -> bool { self.cmp(other) == Equal }
+}
+impl Eq for Event {}
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Event) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+impl Ord for Event {
+    // The sweep line meets points in lexicographic order, from the data structures chapter. Reversed here,
+    // since the queue hands out its greatest element and we want the leftmost point
+    fn cmp(&self, other: &Event) -> Ordering { lexicographic(&other.point, &self.point) }
+}
+
+// Height of the segment on the vertical line through `p`. A vertical segment
+// is taken to be as high as `p` itself, which puts it right where `p` is
+fn height_at(s: &Segment, p: &Point<f64>) -> f64 {
+    let &Segment(a, b) = s;
+    if a.x == b.x { p.y.max(a.y).min(b.y) } else { a.y + (b.y - a.y) * (p.x - a.x) / (b.x - a.x) }
+}
+
+fn slope(s: &Segment) -> f64 {
+    let &Segment(a, b) = s;
+    if a.x == b.x { INFINITY } else { (b.y - a.y) / (b.x - a.x) }
+}
+
+fn all_intersections(input: &[Segment]) -> Vec<(uint, uint)> {
+    // Every segment goes from the end met first by the sweep line to the other one
+    let segments: Vec<Segment> = input.iter().map(|&Segment(a, b)| {
+        if lexicographic(&a, &b) == Greater { Segment(b, a) } else { Segment(a, b) }
+    }).collect();
+    let scale = segments.iter().fold(1.0f64, |m, &Segment(a, b)| {
+        m.max(a.x.abs()).max(a.y.abs()).max(b.x.abs()).max(b.y.abs())
+    });
+    let tolerance = EPSILON * scale;
+
+    let mut queue = PriorityQueue::new();
+    for (i, &Segment(a, b)) in segments.iter().enumerate() {
+        queue.push(Event { point: a, start: Some(i) });
+        queue.push(Event { point: b, start: None });
+    }
+
+    let mut status: Option<Box<StatusNode>> = None;
+    let mut rng = task_rng();
+    let mut found = HashSet::new();
+
+    while !queue.is_empty() {
+        // All the events at the same point are handled at once
+        let event = queue.pop().unwrap();
+        let p = event.point;
+        let mut starting: Vec<uint> = event.start.move_iter().collect();
+        while queue.top().map_or(false, |e| lexicographic(&e.point, &p) == Equal) {
+            starting.extend(queue.pop().unwrap().start.move_iter());
+        }
+
+        // The segments of the status going through p are next to each other
+        let (below, rest) = split(status.take(), &mut |s| height_at(&segments[s], &p) < p.y - tolerance);
+        let (middle, above) = split(rest, &mut |s| height_at(&segments[s], &p) <= p.y + tolerance);
+        let mut through = Vec::new();
+        in_order(&middle, &mut through);
+
+        let mut involved = starting.clone();
+        involved.push_all(through.as_slice());
+        for i in range(0, involved.len()) {
+            for j in range(i + 1, involved.len()) {
+                let (a, b) = (involved[i], involved[j]);
+                found.insert((a.min(b), a.max(b)));
+            }
+        }
+
+        // Segments ending at p leave, the others are put back in the order they have right after p.
+        // A segment reduced to the point p starts and ends there, so it never enters
+        let mut entering: Vec<uint> = through.iter().chain(starting.iter()).map(|&i| i).filter(|&i| {
+            let Segment(_, end) = segments[i];
+            lexicographic(&end, &p) != Equal
+        }).collect();
+        entering.sort_by(|&a, &b| slope(&segments[a]).partial_cmp(&slope(&segments[b])).unwrap_or(Equal));
+
+        // Only the segments that just became neighbours need to be tested
+        let (under, over) = (last(&below), first(&above));
+        if entering.is_empty() {
+            match (under, over) {
+                (Some(s1), Some(s2)) => find_event(segments.as_slice(), s1, s2, &p, &mut queue),
+                _ => ()
+            }
+        } else {
+            for &s in under.iter() {
+                find_event(segments.as_slice(), s, entering[0], &p, &mut queue);
+            }
+            for &s in over.iter() {
+                find_event(segments.as_slice(), entering[entering.len() - 1], s, &p, &mut queue);
+            }
+        }
+
+        let mut reordered = None;
+        for &s in entering.iter() {
+            let node = box StatusNode { segment: s, priority: rng.gen(), left: None, right: None };
+            reordered = merge(reordered, Some(node));
+        }
+        status = merge(merge(below, reordered), above);
+    }
+
+    let mut pairs: Vec<(uint, uint)> = found.move_iter().collect();
+    pairs.sort();
+    pairs
+}
+
+// A node of the treap: its segments are in order, and no node has a greater priority than its parent
+struct StatusNode {
+    segment: uint,
+    priority: u32,
+    left: Option<Box<StatusNode>>,
+    right: Option<Box<StatusNode>>
+}
+
+// Splits the tree into the segments for which `lower` holds, then the others
+fn split(tree: Option<Box<StatusNode>>, lower: &mut |uint| -> bool)
+         -> (Option<Box<StatusNode>>, Option<Box<StatusNode>>) {
+    match tree {
+        None => (None, None),
+        Some(mut node) => {
+            if (*lower)(node.segment) {
+                let (l, r) = split(node.right.take(), lower);
+                node.right = l;
+                (Some(node), r)
+            } else {
+                let (l, r) = split(node.left.take(), lower);
+                node.left = r;
+                (l, Some(node))
+            }
+        }
+    }
+}
+
+// Every segment of `first` goes before those of `second`
+fn merge(first: Option<Box<StatusNode>>, second: Option<Box<StatusNode>>) -> Option<Box<StatusNode>> {
+    match (first, second) {
+        (None, tree) | (tree, None) => tree,
+        (Some(mut a), Some(mut b)) => {
+            if a.priority > b.priority {
+                let right = a.right.take();
+                a.right = merge(right, Some(b));
+                Some(a)
+            } else {
+                let left = b.left.take();
+                b.left = merge(Some(a), left);
+                Some(b)
+            }
+        }
+    }
+}
+
+fn first(tree: &Option<Box<StatusNode>>) -> Option<uint> {
+    match *tree { Some(ref node) => first(&node.left).or(Some(node.segment)), None => None }
+}
+
+fn last(tree: &Option<Box<StatusNode>>) -> Option<uint> {
+    match *tree { Some(ref node) => last(&node.right).or(Some(node.segment)), None => None }
+}
+
+fn in_order(tree: &Option<Box<StatusNode>>, out: &mut Vec<uint>) {
+    match *tree {
+        Some(ref node) => {
+            in_order(&node.left, out);
+            out.push(node.segment);
+            in_order(&node.right, out);
+        },
+        None => ()
+    }
+}
+
+// Crossings on the left of the sweep line have already been handled
+fn find_event(segments: &[Segment], s1: uint, s2: uint, p: &Point<f64>, queue: &mut PriorityQueue<Event>) {
+    match intersect_segments(&segments[s1], &segments[s2]) {
+        Crossing(q) if lexicographic(&q, p) == Greater => queue.push(Event { point: q, start: None }),
+        _ => ()
+    }
+}
+
+let layout = vec![Segment(Point { x: 0.0, y: 0.0 }, Point { x: 4.0, y: 4.0 }),
+                  Segment(Point { x: 0.0, y: 4.0 }, Point { x: 4.0, y: 0.0 }),
+                  Segment(Point { x: 5.0, y: 0.0 }, Point { x: 6.0, y: 1.0 })];
+assert!(all_intersections(layout.as_slice()) == vec![(0, 1)]);
+
+// Board coordinates in nanometres, where crossings are rounded by more than 1e-9
+let board = vec![Segment(Point { x: 567769.0, y: 249246.0 }, Point { x: 561122.0, y: 725284.0 }),
+                 Segment(Point { x: 138650.0, y: 727527.0 }, Point { x: 818730.0, y: 186351.0 }),
+                 Segment(Point { x: 971869.0, y: 465142.0 }, Point { x: 463045.0, y: 534786.0 })];
+assert!(all_intersections(board.as_slice()) == vec![(0, 1), (0, 2)]);
+
+// A segment reduced to a point only meets what goes through that point
+let specks = vec![Segment(Point { x: 0.0, y: 0.0 }, Point { x: 0.0, y: 0.0 }),
+                  Segment(Point { x: 2.0, y: 0.0 }, Point { x: 3.0, y: 1.0 }),
+                  Segment(Point { x: 1.0, y: -1.0 }, Point { x: 1.0, y: 1.0 }),
+                  Segment(Point { x: 1.0, y: 0.0 }, Point { x: 1.0, y: 0.0 })];
+assert!(all_intersections(specks.as_slice()) == vec![(2, 3)]);
+
 // Freezing
 
 /*Lending an &-pointer to an object freezes the pointed-to object and prevents 
@@ -34,6 +34,11 @@ let end = box Point { x: start.x + 100.0, y: start.y + 100.0 };
 let rect = &Rectangle(*start, *end);
 let area = rect.area();
 
+// Moving points by hand gets tedious for anything but a translation, the Transform from the
+// methods chapter does the arithmetic. It borrows the point, hence the `&*` on the box
+let end = box Transform::translate(100.0, 100.0).apply(&*start);
+let rotated = Rectangle(*start, *end).transform(&Transform::rotate(0.5));
+
 /*You can write an expression that dereferences any number of pointers automatically. 
 For example, if you feel inclined, you could write something silly like*/
 
@@ -81,4 +81,488 @@ let score = match numbers {
 };
 
 // Note: Both vectors and strings support a number of useful methods, 
-// defined in std::vec, std::slice, and std::str.
\ No newline at end of file
+// defined in std::vec, std::slice, and std::str.
+
+// -- Working with vectors of points
+
+/*Slices are the natural argument for an algorithm that only reads a set of values. The convex hull of 
+scattered points is computed with Andrew's monotone chain: once the points are sorted, the lower and upper 
+halves of the hull are built by pushing points on a vector and popping the ones that would make a turn 
+the wrong way. Point, the Polygon variant of Shape, orientation and lexicographic are the ones from the data 
+structures chapter.*/
+
+use std::cmp::Equal;
+
+/* The hull comes out counter-clockwise, without duplicated or collinear vertices. Fewer than three
+distinct points, or points that are all aligned, give a degenerate polygon with 0, 1 or 2 vertices. */
+fn convex_hull(points: &[Point<f64>]) -> Shape<f64> {
+    let mut sorted: Vec<Point<f64>> = points.iter().map(|&p| p).collect();
+    sorted.sort_by(|a, b| lexicographic(a, b));
+    let mut unique: Vec<Point<f64>> = Vec::new();
+    for p in sorted.move_iter() {
+        match unique.last() {
+            Some(last) if last.x == p.x && last.y == p.y => continue,
+            _ => ()
+        }
+        unique.push(p);
+    }
+    if unique.len() < 3 {
+        return Polygon(unique);
+    }
+
+    let mut hull: Vec<Point<f64>> = Vec::new();
+    // Lower half, left to right
+    for p in unique.iter() {
+        while hull.len() >= 2 && orientation(hull[hull.len() - 2], hull[hull.len() - 1], *p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+    // Upper half, right to left, without popping into the lower half
+    let lower_len = hull.len() + 1;
+    for p in unique.iter().rev().skip(1) {
+        while hull.len() >= lower_len && orientation(hull[hull.len() - 2], hull[hull.len() - 1], *p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+    // The first point has been pushed again at the very end
+    hull.pop();
+    Polygon(hull)
+}
+
+let measures = vec![Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 }, Point { x: 2.0, y: 2.0 },
+                    Point { x: 2.0, y: 0.0 }, Point { x: 0.0, y: 2.0 }, Point { x: 1.0, y: 0.0 },
+                    Point { x: 2.0, y: 2.0 }];
+let footprint = convex_hull(measures.as_slice()); // the square, corners only
+
+
+/*Fixed-size vectors are handy for small records of a known length, like the three corners of a triangle. A Delaunay 
+triangulation connects a set of points with triangles whose circumcircles contain none of the other points. It is built 
+here by Bowyer-Watson: start from one triangle, then insert the points one at a time, each one replacing the triangles 
+whose circumcircle it falls into by a fan around itself. Points outside of the triangles built so far are handled with 
+ghost triangles, one per edge of the hull, joining it to a vertex at infinity. The circumcircle of a ghost triangle is 
+the half-plane beyond its edge, together with the edge itself. Unlike a huge super triangle whose corners are merely far 
+away, this gives the exact hull even for points that are almost aligned.
+
+Triangles are given as indices in the original vector, counter-clockwise. Duplicated points are only used once, at 
+the index of their first occurrence. When all the points are aligned there is nothing to triangulate and the result 
+is empty.*/
+
+use std::collections::HashMap;
+
+struct Triangulation {
+    triangles: Vec<[uint, ..3]>,
+    // neighbours[t][i] is the triangle across the edge opposite to corner i of triangle t
+    neighbours: Vec<[Option<uint>, ..3]>
+}
+
+// Positive when d is inside the circumcircle of the counter-clockwise triangle (a, b, c)
+fn in_circle(a: &Point<f64>, b: &Point<f64>, c: &Point<f64>, d: &Point<f64>) -> f64 {
+    let (ax, ay) = (a.x - d.x, a.y - d.y);
+    let (bx, by) = (b.x - d.x, b.y - d.y);
+    let (cx, cy) = (c.x - d.x, c.y - d.y);
+    (ax * ax + ay * ay) * (bx * cy - cx * by)
+        - (bx * bx + by * by) * (ax * cy - cx * ay)
+        + (cx * cx + cy * cy) * (ax * by - bx * ay)
+}
+
+fn delaunay(points: &Vec<Point<f64>>) -> Triangulation {
+    let n = points.len();
+
+    // Indices of the distinct points
+    let mut order: Vec<uint> = range(0, n).collect();
+    order.sort_by(|&i, &j| lexicographic(&points[i], &points[j]));
+    let mut distinct: Vec<uint> = Vec::new();
+    for &i in order.iter() {
+        match distinct.last() {
+            Some(&last) if points[last].x == points[i].x && points[last].y == points[i].y => continue,
+            _ => ()
+        }
+        distinct.push(i);
+    }
+    // Keep the first occurrence of each point
+    distinct.sort();
+
+    let aligned = distinct.len() < 3 || distinct.iter().all(|&i| {
+        orientation(points[distinct[0]], points[distinct[1]], points[i]) == 0.0
+    });
+    if aligned {
+        return Triangulation { triangles: Vec::new(), neighbours: Vec::new() };
+    }
+
+    // The first triangle, counter-clockwise, from two points and the first one not aligned with them
+    let (a, b) = (distinct[0], distinct[1]);
+    let c = *distinct.iter().find(|&&i| orientation(points[a], points[b], points[i]) != 0.0).unwrap();
+    let (b, c) = if orientation(points[a], points[b], points[c]) > 0.0 { (b, c) } else { (c, b) };
+
+    // The vertex at infinity gets the index n, past the real points. It always comes last in a ghost triangle,
+    // whose edge has the outside of the hull on its left
+    let infinity = n;
+    let mut triangles: Vec<[uint, ..3]> = vec![[a, b, c], [b, a, infinity], [c, b, infinity], [a, c, infinity]];
+    let conflicts = |t: &[uint, ..3], p: &Point<f64>| -> bool {
+        let (u, v) = (&points[t[0]], &points[t[1]]);
+        if t[2] != infinity {
+            return in_circle(u, v, &points[t[2]], p) > 0.0;
+        }
+        let side = orientation(*u, *v, *p);
+        side > 0.0 || (side == 0.0 && (p.x - u.x) * (v.x - u.x) + (p.y - u.y) * (v.y - u.y) > 0.0
+                                   && (p.x - v.x) * (u.x - v.x) + (p.y - v.y) * (u.y - v.y) > 0.0)
+    };
+
+    for &i in distinct.iter().filter(|&&i| i != a && i != b && i != c) {
+        let p = points[i];
+        let (bad, good) = triangles.partition(|t| conflicts(t, &p));
+
+        // The edges of the hole left by the bad triangles are the ones they do not share
+        let mut edges: Vec<(uint, uint)> = Vec::new();
+        for t in bad.iter() {
+            for k in range(0u, 3) {
+                let (a, b) = (t[k], t[(k + 1) % 3]);
+                let shared = bad.iter().any(|o| range(0u, 3).any(|m| o[m] == b && o[(m + 1) % 3] == a));
+                if !shared { edges.push((a, b)); }
+            }
+        }
+
+        triangles = good;
+        for &(a, b) in edges.iter() {
+            // Rotated so that the vertex at infinity stays last
+            triangles.push(if a == infinity { [b, i, a] } else if b == infinity { [i, a, b] } else { [a, b, i] });
+        }
+    }
+    triangles.retain(|t| t.iter().all(|&v| v < n));
+
+    // Each directed edge belongs to a single triangle, its neighbour owns the reversed edge
+    let mut owners = HashMap::new();
+    for (t, corners) in triangles.iter().enumerate() {
+        for k in range(0u, 3) {
+            owners.insert((corners[k], corners[(k + 1) % 3]), t);
+        }
+    }
+    let neighbours = triangles.iter().map(|corners| {
+        let mut across = [None, None, None];
+        for k in range(0u, 3) {
+            // The edge opposite to corner k goes from the next corner to the one after
+            let (a, b) = (corners[(k + 1) % 3], corners[(k + 2) % 3]);
+            across[k] = owners.find(&(b, a)).map(|&t| t);
+        }
+        across
+    }).collect();
+
+    Triangulation { triangles: triangles, neighbours: neighbours }
+}
+
+let samples = vec![Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 0.0 }, Point { x: 0.0, y: 1.0 },
+                   Point { x: 1.0, y: 1.0 }, Point { x: 1.0, y: 1.0 }, Point { x: 0.5, y: 2.0 }];
+let mesh = delaunay(&samples);
+
+// Almost aligned points still give a triangulation of their whole hull
+let sliver = vec![Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 0.0 }, Point { x: 2.0, y: 0.0 },
+                  Point { x: 3.0, y: 0.0 }, Point { x: 1.5, y: 0.01 }];
+assert!(delaunay(&sliver).triangles.len() == 3);
+
+
+/*The Voronoi cell of a site is the part of the plane closer to it than to any other site. Each cell is convex: it is 
+what remains of the bounding rectangle once cut by the bisector between the site and each of its Delaunay neighbours, 
+which are exactly the sites sharing an edge of the cell. Sites left out of the triangulation are either duplicates, 
+whose cell is empty since it already belongs to the first occurrence, or aligned sites, which are cut by every other 
+site instead. The cells then cover the bounding rectangle without overlapping. Aabb is the bounding box type from the generics chapter.*/
+
+// Keeps the part of the convex polygon `cell` that is closer to `site` than to `other`
+fn clip_by_bisector(cell: Vec<Point<f64>>, site: &Point<f64>, other: &Point<f64>) -> Vec<Point<f64>> {
+    let (mid_x, mid_y) = ((site.x + other.x) / 2.0, (site.y + other.y) / 2.0);
+    let side = |p: &Point<f64>| (p.x - mid_x) * (other.x - site.x) + (p.y - mid_y) * (other.y - site.y);
+    let n = cell.len();
+    let mut clipped = Vec::new();
+    for i in range(0, n) {
+        let (p, q) = (cell[i], cell[(i + 1) % n]);
+        let (sp, sq) = (side(&p), side(&q));
+        if sp <= 0.0 {
+            clipped.push(p);
+        }
+        if (sp < 0.0 && sq > 0.0) || (sp > 0.0 && sq < 0.0) {
+            let t = sp / (sp - sq);
+            clipped.push(Point { x: p.x + t * (q.x - p.x), y: p.y + t * (q.y - p.y) });
+        }
+    }
+    clipped
+}
+
+// One Polygon per site, in the same order
+fn voronoi(sites: &Vec<Point<f64>>, bounds: &Aabb) -> Vec<Shape<f64>> {
+    let mesh = delaunay(sites);
+    let mut adjacent: Vec<Vec<uint>> = Vec::from_fn(sites.len(), |_| Vec::new());
+    for corners in mesh.triangles.iter() {
+        for k in range(0u, 3) {
+            adjacent.get_mut(corners[k]).push(corners[(k + 1) % 3]);
+            adjacent.get_mut(corners[k]).push(corners[(k + 2) % 3]);
+        }
+    }
+
+    range(0, sites.len()).map(|i| {
+        let site = sites[i];
+        let (lo, hi) = (bounds.min, bounds.max);
+        let mut cell = vec![lo, Point { x: hi.x, y: lo.y }, hi, Point { x: lo.x, y: hi.y }];
+        if adjacent[i].is_empty() && range(0, i).any(|j| sites[j].x == site.x && sites[j].y == site.y) {
+            return Polygon(Vec::new());
+        }
+        let others: Vec<uint> = if adjacent[i].is_empty() { range(0, sites.len()).collect() } else { adjacent[i].clone() };
+        for &j in others.iter() {
+            let other = sites[j];
+            if other.x != site.x || other.y != site.y {
+                cell = clip_by_bisector(cell, &site, &other);
+            }
+        }
+        Polygon(cell)
+    }).collect()
+}
+
+// The cells are shapes like any other, so they can be drawn straight away
+let regions = voronoi(&samples, &Aabb { min: Point { x: -1.0, y: -1.0 }, max: Point { x: 2.0, y: 3.0 } });
+let mut svg = Svg::new(300.0, 400.0);
+let cells: Vec<Box<Drawable>> = regions.move_iter().map(|region| box region as Box<Drawable>).collect();
+draw_all(cells.as_slice(), &mut svg);
+
+// Every point of the rectangle is in exactly one cell, so their areas add up to its own (signed_area is from the
+// data structures chapter)
+let frame = Aabb { min: Point { x: -1.0, y: -1.0 }, max: Point { x: 4.0, y: 1.0 } };
+let total = voronoi(&sliver, &frame).iter().fold(0.0, |sum, cell| match *cell {
+    Polygon(ref points) => sum + signed_area(points.as_slice()).abs(),
+    _ => sum
+});
+assert!((total - 10.0).abs() < 1e-9);
+
+
+/*A vector of rectangles can describe any region made of axis-aligned pieces, like the damaged parts of a window or 
+the free space on a floor plan. Keeping the pieces disjoint makes the area a plain sum. Everything is built on one 
+operation, cutting a rectangle out of another, which leaves at most four pieces: the strips below and above the 
+cut across the whole width, then the strips on its left and right between them. Aabb is the bounding box type 
+from the generics chapter, Bounded comes from there as well.*/
+
+struct Region {
+    rects: Vec<Aabb>
+}
+
+fn has_area(r: &Aabb) -> bool {
+    r.min.x < r.max.x && r.min.y < r.max.y
+}
+
+fn intersect_rect(a: &Aabb, b: &Aabb) -> Option<Aabb> {
+    let r = Aabb { min: Point { x: a.min.x.max(b.min.x), y: a.min.y.max(b.min.y) },
+                   max: Point { x: a.max.x.min(b.max.x), y: a.max.y.min(b.max.y) } };
+    if has_area(&r) { Some(r) } else { None }
+}
+
+// The parts of `a` outside of `b`, disjoint from each other
+fn subtract_rect(a: &Aabb, b: &Aabb) -> Vec<Aabb> {
+    let cut = match intersect_rect(a, b) {
+        Some(cut) => cut,
+        None => return vec![*a]
+    };
+    let (lo, hi) = (a.min, a.max);
+    let pieces = [
+        Aabb { min: lo, max: Point { x: hi.x, y: cut.min.y } },
+        Aabb { min: Point { x: lo.x, y: cut.max.y }, max: hi },
+        Aabb { min: Point { x: lo.x, y: cut.min.y }, max: Point { x: cut.min.x, y: cut.max.y } },
+        Aabb { min: Point { x: cut.max.x, y: cut.min.y }, max: Point { x: hi.x, y: cut.max.y } }
+    ];
+    pieces.iter().filter(|r| has_area(*r)).map(|r| *r).collect()
+}
+
+impl Region {
+    fn new() -> Region { Region { rects: Vec::new() } }
+
+    fn from_rect(r: &Aabb) -> Region {
+        let r = Aabb::from_corners(r.min, r.max);
+        Region { rects: if has_area(&r) { vec![r] } else { Vec::new() } }
+    }
+
+    fn difference(&self, other: &Region) -> Region {
+        let mut rects = Vec::new();
+        for r in self.rects.iter() {
+            let mut pieces = vec![*r];
+            for cut in other.rects.iter() {
+                pieces = pieces.iter().flat_map(|piece| subtract_rect(piece, cut).move_iter()).collect();
+            }
+            rects.push_all_move(pieces);
+        }
+        Region { rects: rects }
+    }
+
+    // Both sides are disjoint, so are the pairwise intersections
+    fn intersection(&self, other: &Region) -> Region {
+        let mut rects = Vec::new();
+        for a in self.rects.iter() {
+            for b in other.rects.iter() {
+                match intersect_rect(a, b) {
+                    Some(r) => rects.push(r),
+                    None => ()
+                }
+            }
+        }
+        Region { rects: rects }
+    }
+
+    // Only what `other` adds is kept from it, so that nothing is counted twice
+    fn union(&self, other: &Region) -> Region {
+        let mut rects: Vec<Aabb> = self.rects.iter().map(|r| *r).collect();
+        rects.push_all_move(other.difference(self).rects);
+        Region { rects: rects }
+    }
+
+    fn xor(&self, other: &Region) -> Region {
+        let mut rects = self.difference(other).rects;
+        rects.push_all_move(other.difference(self).rects);
+        Region { rects: rects }
+    }
+
+    fn area(&self) -> f64 {
+        self.rects.iter().fold(0.0, |sum, r| sum + (r.max.x - r.min.x) * (r.max.y - r.min.y))
+    }
+
+    fn contains(&self, p: &Point<f64>) -> bool {
+        self.rects.iter().any(|r| r.min.x <= p.x && p.x <= r.max.x && r.min.y <= p.y && p.y <= r.max.y)
+    }
+
+    fn is_empty(&self) -> bool { self.rects.is_empty() }
+}
+
+impl Bounded for Region {
+    fn bounds(&self) -> Aabb {
+        self.rects.iter().fold(Aabb::empty(), |bounds, r| bounds.union(r))
+    }
+}
+
+// What is left of a room once the furniture is in
+let room = Region::from_rect(&Aabb { min: Point { x: 0.0, y: 0.0 }, max: Point { x: 5.0, y: 4.0 } });
+let bed = Region::from_rect(&Aabb { min: Point { x: 0.0, y: 0.0 }, max: Point { x: 2.0, y: 2.0 } });
+let desk = Region::from_rect(&Aabb { min: Point { x: 1.0, y: 3.0 }, max: Point { x: 4.0, y: 4.0 } });
+let free = room.difference(&bed.union(&desk));
+println!("free floor: {}", free.area()); // 20 - 4 - 3 = 13
+assert!(!free.contains(&Point { x: 0.5, y: 0.5 }));
+
+
+/*Packing rectangles into bins, for texture atlases or cutting sheets, keeps one more vector per bin: its free 
+space, as the list of the largest empty rectangles. Unlike the pieces of a Region these overlap each other. Placing 
+a rectangle cuts it out of every free rectangle it touches, each of which leaves up to four new maximal ones, and 
+free rectangles that end up inside another are dropped. The pieces are taken largest side first, each in the bin 
+and free rectangle where it leaves the shortest leftover side (the maxrects heuristic). Sizes are checked with 
+`measure`, and GeometryError is the error type from the data structures chapter. A piece larger than an empty bin 
+does not stop the others from being packed, its index is reported with the result.*/
+
+struct Placement {
+    index: uint,      // position of the piece in the list given to `pack`
+    bin: uint,
+    rect: Aabb,
+    rotated: bool     // whether width and height were swapped to make it fit
+}
+
+struct Packing {
+    placements: Vec<Placement>,
+    unplaced: Vec<uint>   // pieces that fit in no bin, in the order of `sizes`
+}
+
+fn encloses(outer: &Aabb, inner: &Aabb) -> bool {
+    outer.min.x <= inner.min.x && outer.min.y <= inner.min.y &&
+    inner.max.x <= outer.max.x && inner.max.y <= outer.max.y
+}
+
+// Free rectangle, orientation and leftover short side of the tightest fit
+fn best_fit(free: &[Aabb], w: f64, h: f64, rotate: bool) -> Option<(uint, bool, f64)> {
+    let mut best: Option<(uint, bool, f64)> = None;
+    for (i, r) in free.iter().enumerate() {
+        let (fw, fh) = (r.max.x - r.min.x, r.max.y - r.min.y);
+        for &(pw, ph, rotated) in [(w, h, false), (h, w, true)].iter() {
+            if (rotated && !rotate) || pw > fw || ph > fh {
+                continue;
+            }
+            let leftover = (fw - pw).min(fh - ph);
+            if best.map_or(true, |(_, _, other)| leftover < other) {
+                best = Some((i, rotated, leftover));
+            }
+        }
+    }
+    best
+}
+
+fn cut_free(free: &Vec<Aabb>, used: &Aabb) -> Vec<Aabb> {
+    let mut next = Vec::new();
+    for r in free.iter() {
+        if intersect_rect(r, used).is_none() {
+            next.push(*r);
+            continue;
+        }
+        let pieces = [
+            Aabb { min: r.min, max: Point { x: used.min.x, y: r.max.y } },
+            Aabb { min: Point { x: used.max.x, y: r.min.y }, max: r.max },
+            Aabb { min: r.min, max: Point { x: r.max.x, y: used.min.y } },
+            Aabb { min: Point { x: r.min.x, y: used.max.y }, max: r.max }
+        ];
+        for piece in pieces.iter().filter(|piece| has_area(*piece)) {
+            next.push(*piece);
+        }
+    }
+    // Of two identical rectangles, the first one is kept
+    range(0, next.len()).filter(|&i| {
+        !range(0, next.len()).any(|j| j != i && encloses(&next[j], &next[i]) && (j < i || !encloses(&next[i], &next[j])))
+    }).map(|i| next[i]).collect()
+}
+
+// Placements come back in the order of `sizes`, only the dimensions of the given rectangles matter
+fn pack(sizes: &[Aabb], bin_width: f64, bin_height: f64, rotate: bool) -> Result<Packing, GeometryError> {
+    let (bin_width, bin_height) = (try!(measure(bin_width)), try!(measure(bin_height)));
+    let mut pieces = Vec::new();
+    let mut unplaced = Vec::new();
+    for (i, r) in sizes.iter().enumerate() {
+        let (w, h) = (try!(measure((r.max.x - r.min.x).abs())), try!(measure((r.max.y - r.min.y).abs())));
+        let fits = (w <= bin_width && h <= bin_height) || (rotate && h <= bin_width && w <= bin_height);
+        if fits { pieces.push((i, w, h)) } else { unplaced.push(i) }
+    }
+    pieces.sort_by(|&(_, w1, h1), &(_, w2, h2)| w2.max(h2).partial_cmp(&w1.max(h1)).unwrap_or(Equal));
+
+    let whole = Aabb { min: Point { x: 0.0, y: 0.0 }, max: Point { x: bin_width, y: bin_height } };
+    let mut bins: Vec<Vec<Aabb>> = Vec::new();
+    let mut placements = Vec::new();
+    for &(index, w, h) in pieces.iter() {
+        let mut best: Option<(uint, uint, bool, f64)> = None;
+        for (b, free) in bins.iter().enumerate() {
+            match best_fit(free.as_slice(), w, h, rotate) {
+                Some((i, rotated, leftover)) if best.map_or(true, |(_, _, _, other)| leftover < other) =>
+                    best = Some((b, i, rotated, leftover)),
+                _ => ()
+            }
+        }
+        let (b, i, rotated) = match best {
+            Some((b, i, rotated, _)) => (b, i, rotated),
+            None => {
+                // Nothing left anywhere, a new bin always fits since the sizes were checked
+                bins.push(vec![whole]);
+                let (i, rotated, _) = best_fit([whole], w, h, rotate).unwrap();
+                (bins.len() - 1, i, rotated)
+            }
+        };
+        let (pw, ph) = if rotated { (h, w) } else { (w, h) };
+        let corner = bins[b][i].min;
+        let rect = Aabb { min: corner, max: Point { x: corner.x + pw, y: corner.y + ph } };
+        let free = cut_free(&bins[b], &rect);
+        *bins.get_mut(b) = free;
+        placements.push(Placement { index: index, bin: b, rect: rect, rotated: rotated });
+    }
+    placements.sort_by(|a, b| a.index.cmp(&b.index));
+    Ok(Packing { placements: placements, unplaced: unplaced })
+}
+
+let sprites = [Aabb { min: Point { x: 0.0, y: 0.0 }, max: Point { x: 64.0, y: 32.0 } },
+               Aabb { min: Point { x: 0.0, y: 0.0 }, max: Point { x: 32.0, y: 128.0 } },
+               Aabb { min: Point { x: 0.0, y: 0.0 }, max: Point { x: 100.0, y: 100.0 } }];
+match pack(sprites, 128.0, 128.0, true) {
+    Ok(atlas) => {
+        for p in atlas.placements.iter() {
+            println!("sprite {} in sheet {} at ({}, {}), rotated: {}", p.index, p.bin, p.rect.min.x, p.rect.min.y, p.rotated);
+        }
+        for i in atlas.unplaced.iter() {
+            println!("sprite {} is larger than a sheet", i);
+        }
+    },
+    Err(e) => println!("cannot pack: {}", e)
+}
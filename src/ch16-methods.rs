@@ -5,27 +5,911 @@ languages. Methods are called with dot notation, as in my_vec.len().
 Implementations, written with the impl keyword, can define methods on most Rust types, 
 including structs and enums. As an example, let's define a draw method on our Shape enum.*/
 
-struct Point {
-    x: f64,
-    y: f64
+struct Point<T> {
+    x: T,
+    y: T
 }
 
-enum Shape {
-    Circle(Point, f64),
-    Rectangle(Point, Point)
+// The same Shape as in the data structures chapter. Drawing happens on floating point coordinates,
+// so the methods are defined for Shape<f64>
+enum Shape<T> {
+    Circle(Point<T>, T),
+    Rectangle(Point<T>, Point<T>),
+    Polygon(Vec<Point<T>>),
+    Ellipse(Point<T>, T, T),
+    Triangle(Point<T>, Point<T>, Point<T>)
 }
 
-impl Shape {
-    fn draw(&self) {
+// Anything shapes can be drawn on, an image file as well as a terminal
+trait Canvas {
+    fn circle(&mut self, center: Point<f64>, radius: f64);
+    fn rect(&mut self, p1: Point<f64>, p2: Point<f64>);
+    fn ellipse(&mut self, center: Point<f64>, rx: f64, ry: f64);
+    fn polygon(&mut self, points: &[Point<f64>]);
+    // Like a polygon, without the edge from the last point back to the first one
+    fn polyline(&mut self, points: &[Point<f64>]);
+}
+
+/*Distances from (x, y) to the outline of a shape. Apart from the one to a polyline they are signed:
+negative inside the shape, positive outside. The canvases that rasterize only care about the magnitude.
+Edges are measured with Segment and segment_distance from the references chapter.*/
+
+fn rect_distance(p1: Point<f64>, p2: Point<f64>, x: f64, y: f64) -> f64 {
+    let dx = (p1.x.min(p2.x) - x).max(x - p1.x.max(p2.x));
+    let dy = (p1.y.min(p2.y) - y).max(y - p1.y.max(p2.y));
+    if dx > 0.0 || dy > 0.0 {
+        // outside, distance to the closest edge or corner
+        (dx.max(0.0) * dx.max(0.0) + dy.max(0.0) * dy.max(0.0)).sqrt()
+    } else {
+        // inside, distance to the closest edge
+        dx.max(dy)
+    }
+}
+
+// Even-odd rule: a ray going right from (x, y) crosses the outline an odd number of times from the inside
+fn polygon_encloses(points: &[Point<f64>], x: f64, y: f64) -> bool {
+    let n = points.len();
+    let mut inside = false;
+    for i in range(0, n) {
+        let (a, b) = (points[i], points[(i + n - 1) % n]);
+        if (a.y > y) != (b.y > y) && x < (b.x - a.x) * (y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn polyline_distance(points: &[Point<f64>], x: f64, y: f64) -> f64 {
+    let p = Point { x: x, y: y };
+    let mut distance = std::f64::INFINITY;
+    for i in range(1, points.len()) {
+        distance = distance.min(segment_distance(&Segment(points[i - 1], points[i]), &p));
+    }
+    distance
+}
+
+fn polygon_distance(points: &[Point<f64>], x: f64, y: f64) -> f64 {
+    let (n, p) = (points.len(), Point { x: x, y: y });
+    let mut distance = std::f64::INFINITY;
+    for i in range(0, n) {
+        distance = distance.min(segment_distance(&Segment(points[i], points[(i + 1) % n]), &p));
+    }
+    if polygon_encloses(points, x, y) { -distance } else { distance }
+}
+
+// The exact distance to an ellipse has no closed form, dividing the implicit equation
+// by the length of its gradient is a good estimate close to the outline
+fn ellipse_distance(center: Point<f64>, rx: f64, ry: f64, x: f64, y: f64) -> f64 {
+    let (u, v) = ((x - center.x) / rx, (y - center.y) / ry);
+    let f = u * u + v * v - 1.0;
+    let (gx, gy) = (2.0 * u / rx, 2.0 * v / ry);
+    let gradient = (gx * gx + gy * gy).sqrt();
+    if gradient == 0.0 { -rx.min(ry) } else { f / gradient }
+}
+
+// An SVG document is built up element by element, the whole file is rendered at the end
+struct Svg {
+    width: f64,
+    height: f64,
+    elements: Vec<String>
+}
+
+impl Svg {
+    fn new(width: f64, height: f64) -> Svg {
+        Svg { width: width, height: height, elements: Vec::new() }
+    }
+
+    fn to_string(&self) -> String {
+        let mut out = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+                              self.width, self.height);
+        for element in self.elements.iter() {
+            out.push_str("  ");
+            out.push_str(element.as_slice());
+            out.push_str("\n");
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+impl Canvas for Svg {
+    fn circle(&mut self, center: Point<f64>, radius: f64) {
+        self.elements.push(format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"black\" />",
+                                   center.x, center.y, radius));
+    }
+
+    fn rect(&mut self, p1: Point<f64>, p2: Point<f64>) {
+        // SVG wants the top left corner and a positive size, whatever the order of the corners
+        let (x, y) = (p1.x.min(p2.x), p1.y.min(p2.y));
+        let (width, height) = ((p2.x - p1.x).abs(), (p2.y - p1.y).abs());
+        self.elements.push(format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"black\" />",
+                                   x, y, width, height));
+    }
+
+    fn ellipse(&mut self, center: Point<f64>, rx: f64, ry: f64) {
+        self.elements.push(format!("<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" fill=\"none\" stroke=\"black\" />",
+                                   center.x, center.y, rx, ry));
+    }
+
+    fn polygon(&mut self, points: &[Point<f64>]) {
+        let coordinates: Vec<String> = points.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+        self.elements.push(format!("<polygon points=\"{}\" fill=\"none\" stroke=\"black\" />",
+                                   coordinates.connect(" ")));
+    }
+
+    fn polyline(&mut self, points: &[Point<f64>]) {
+        let coordinates: Vec<String> = points.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+        self.elements.push(format!("<polyline points=\"{}\" fill=\"none\" stroke=\"black\" />",
+                                   coordinates.connect(" ")));
+    }
+}
+
+/*A grid of characters works as a canvas too, which is handy to look at a scene from a terminal.
+Terminal cells are about twice as tall as they are wide: `aspect` is the height of a cell divided
+by its width, so that circles do not come out squashed.*/
+
+struct AsciiCanvas {
+    columns: uint,
+    rows: uint,
+    scale: f64,  // world units covered by the width of one cell
+    aspect: f64,
+    cells: Vec<char>
+}
+
+impl AsciiCanvas {
+    fn new(columns: uint, rows: uint, scale: f64, aspect: f64) -> AsciiCanvas {
+        assert!(columns > 0 && rows > 0, "an ASCII canvas needs at least one cell");
+        AsciiCanvas { columns: columns, rows: rows, scale: scale, aspect: aspect,
+                      cells: Vec::from_elem(columns * rows, ' ') }
+    }
+
+    // Marks every cell for which `hit` returns true, given the center and the half size of the cell
+    fn plot(&mut self, hit: |x: f64, y: f64, half_w: f64, half_h: f64| -> bool) {
+        let (cell_w, cell_h) = (self.scale, self.scale * self.aspect);
+        for row in range(0, self.rows) {
+            for column in range(0, self.columns) {
+                let x = (column as f64 + 0.5) * cell_w;
+                let y = (row as f64 + 0.5) * cell_h;
+                if hit(x, y, cell_w / 2.0, cell_h / 2.0) {
+                    *self.cells.get_mut(row * self.columns + column) = '*';
+                }
+            }
+        }
+    }
+
+    fn print(&self) {
+        for line in self.cells.as_slice().chunks(self.columns) {
+            println!("{}", line.iter().map(|&c| c).collect::<String>());
+        }
+    }
+}
+
+impl Canvas for AsciiCanvas {
+    fn circle(&mut self, center: Point<f64>, radius: f64) {
+        self.plot(|x, y, half_w, half_h| {
+            let (dx, dy) = (x - center.x, y - center.y);
+            // Keep the cells the outline passes through
+            ((dx * dx + dy * dy).sqrt() - radius).abs() <= half_w.max(half_h)
+        });
+    }
+
+    fn rect(&mut self, p1: Point<f64>, p2: Point<f64>) {
+        let (left, right) = (p1.x.min(p2.x), p1.x.max(p2.x));
+        let (top, bottom) = (p1.y.min(p2.y), p1.y.max(p2.y));
+        self.plot(|x, y, half_w, half_h| {
+            let outer = x >= left - half_w && x <= right + half_w && y >= top - half_h && y <= bottom + half_h;
+            let inner = x > left + half_w && x < right - half_w && y > top + half_h && y < bottom - half_h;
+            outer && !inner
+        });
+    }
+
+    fn ellipse(&mut self, center: Point<f64>, rx: f64, ry: f64) {
+        self.plot(|x, y, half_w, half_h| ellipse_distance(center, rx, ry, x, y).abs() <= half_w.max(half_h));
+    }
+
+    fn polygon(&mut self, points: &[Point<f64>]) {
+        self.plot(|x, y, half_w, half_h| polygon_distance(points, x, y).abs() <= half_w.max(half_h));
+    }
+
+    fn polyline(&mut self, points: &[Point<f64>]) {
+        self.plot(|x, y, half_w, half_h| polyline_distance(points, x, y) <= half_w.max(half_h));
+    }
+}
+
+/*For image output without any viewer or library around, shapes can be rasterized into a buffer of RGB
+pixels. Edges are anti-aliased by covering each pixel in proportion to its distance to the outline, and the
+buffer can be saved either as a PPM or as a PNG file.*/
+
+struct Raster {
+    width: uint,
+    height: uint,
+    color: (u8, u8, u8),
+    pixels: Vec<u8>  // RGB triplets, row after row
+}
+
+impl Raster {
+    // PNG has no empty images, so neither does Raster
+    fn new(width: uint, height: uint) -> Raster {
+        assert!(width > 0 && height > 0, "a raster needs at least one pixel");
+        Raster { width: width, height: height, color: (0, 0, 0),
+                 pixels: Vec::from_elem(width * height * 3, 255u8) }
+    }
+
+    // Draws a one pixel wide line, `distance` tells how far a point is from the outline
+    fn stroke(&mut self, distance: |x: f64, y: f64| -> f64) {
+        let (r, g, b) = self.color;
+        for y in range(0, self.height) {
+            for x in range(0, self.width) {
+                let coverage = (1.0 - distance(x as f64 + 0.5, y as f64 + 0.5)).max(0.0).min(1.0);
+                if coverage > 0.0 {
+                    let i = (y * self.width + x) * 3;
+                    self.blend(i, r, coverage);
+                    self.blend(i + 1, g, coverage);
+                    self.blend(i + 2, b, coverage);
+                }
+            }
+        }
+    }
+
+    fn blend(&mut self, i: uint, channel: u8, coverage: f64) {
+        let old = self.pixels[i] as f64;
+        *self.pixels.get_mut(i) = (old + (channel as f64 - old) * coverage).round() as u8;
+    }
+
+    fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        ppm.push_all(self.pixels.as_slice());
+        ppm
+    }
+
+    fn to_png(&self) -> Vec<u8> {
+        // Every row starts with its filter type, 0 meaning no filtering
+        let mut raw = Vec::new();
+        for row in self.pixels.as_slice().chunks(self.width * 3) {
+            raw.push(0u8);
+            raw.push_all(row);
+        }
+
+        // A zlib stream of stored (uncompressed) deflate blocks, of at most 65535 bytes each
+        let mut zlib = vec![0x78u8, 0x01];
+        let blocks: Vec<&[u8]> = raw.as_slice().chunks(65535).collect();
+        for (i, block) in blocks.iter().enumerate() {
+            let len = block.len() as u16;
+            zlib.push(if i + 1 == blocks.len() { 1 } else { 0 });
+            zlib.push_all([len as u8, (len >> 8) as u8, !len as u8, (!len >> 8) as u8]);
+            zlib.push_all(*block);
+        }
+        zlib.push_all(be32(adler32(raw.as_slice())));
+
+        let mut header = Vec::new();
+        header.push_all(be32(self.width as u32));
+        header.push_all(be32(self.height as u32));
+        header.push_all([8u8, 2, 0, 0, 0]); // 8 bits per channel, RGB, no interlacing
+
+        let mut png = vec![137u8, 80, 78, 71, 13, 10, 26, 10];
+        png_chunk(&mut png, b"IHDR", header.as_slice());
+        png_chunk(&mut png, b"IDAT", zlib.as_slice());
+        png_chunk(&mut png, b"IEND", []);
+        png
+    }
+}
+
+impl Canvas for Raster {
+    fn circle(&mut self, center: Point<f64>, radius: f64) {
+        self.stroke(|x, y| {
+            let (dx, dy) = (x - center.x, y - center.y);
+            ((dx * dx + dy * dy).sqrt() - radius).abs()
+        });
+    }
+
+    fn rect(&mut self, p1: Point<f64>, p2: Point<f64>) {
+        self.stroke(|x, y| rect_distance(p1, p2, x, y).abs());
+    }
+
+    fn ellipse(&mut self, center: Point<f64>, rx: f64, ry: f64) {
+        self.stroke(|x, y| ellipse_distance(center, rx, ry, x, y).abs());
+    }
+
+    fn polygon(&mut self, points: &[Point<f64>]) {
+        self.stroke(|x, y| polygon_distance(points, x, y).abs());
+    }
+
+    fn polyline(&mut self, points: &[Point<f64>]) {
+        self.stroke(|x, y| polyline_distance(points, x, y));
+    }
+}
+
+fn be32(n: u32) -> [u8, ..4] {
+    [(n >> 24) as u8, (n >> 16) as u8, (n >> 8) as u8, n as u8]
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for &byte in bytes.iter() {
+        crc ^= byte as u32;
+        for _ in range(0u, 8) {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes.iter() {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+// A PNG chunk is its length, its type, its data and the CRC of the type and data
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+    png.push_all(be32(data.len() as u32));
+    let start = png.len();
+    png.push_all(kind);
+    png.push_all(data);
+    let crc = crc32(png.slice_from(start));
+    png.push_all(be32(crc));
+}
+
+fn draw_circle(canvas: &mut Canvas, center: Point<f64>, radius: f64) { canvas.circle(center, radius) }
+fn draw_rectangle(canvas: &mut Canvas, p1: Point<f64>, p2: Point<f64>) { canvas.rect(p1, p2) }
+
+impl Shape<f64> {
+    fn draw(&self, canvas: &mut Canvas) {
+        match *self {
+            Circle(p, f) => draw_circle(canvas, p, f),
+            Rectangle(p1, p2) => draw_rectangle(canvas, p1, p2),
+            Polygon(ref points) => canvas.polygon(points.as_slice()),
+            Ellipse(p, rx, ry) => canvas.ellipse(p, rx, ry),
+            Triangle(a, b, c) => canvas.polygon([a, b, c])
+        }
+    }
+}
+
+/*Containment is answered from the signed distance to the outline. Whether points lying right on
+the outline count as inside is up to the caller, and so is the tolerance used to decide that a point
+is on the outline: with floating point coordinates, exact comparisons are rarely what is wanted.*/
+
+enum Boundary {
+    Inclusive,
+    Exclusive
+}
+
+impl Shape<f64> {
+    fn signed_distance(&self, p: &Point<f64>) -> f64 {
+        match *self {
+            Circle(center, radius) => {
+                let (dx, dy) = (p.x - center.x, p.y - center.y);
+                (dx * dx + dy * dy).sqrt() - radius
+            },
+            Rectangle(p1, p2) => rect_distance(p1, p2, p.x, p.y),
+            Polygon(ref points) => polygon_distance(points.as_slice(), p.x, p.y),
+            Ellipse(center, rx, ry) => ellipse_distance(center, rx, ry, p.x, p.y),
+            Triangle(a, b, c) => polygon_distance([a, b, c], p.x, p.y)
+        }
+    }
+
+    fn contains_with(&self, p: &Point<f64>, boundary: Boundary, epsilon: f64) -> bool {
+        let distance = self.signed_distance(p);
+        match boundary {
+            Inclusive => distance <= epsilon,
+            Exclusive => distance < -epsilon
+        }
+    }
+
+    fn contains(&self, p: &Point<f64>) -> bool {
+        self.contains_with(p, Inclusive, 0.0)
+    }
+}
+
+let click = Point { x: 4.0, y: 2.0 };
+let button = Rectangle(Point { x: 0.0, y: 0.0 }, Point { x: 4.0, y: 2.0 });
+assert!(button.contains(&click));
+assert!(!button.contains_with(&click, Exclusive, 1e-9));
+
+/*Intersection tests and overlap areas between two shapes. Circles and rectangles have closed forms,
+any other pair goes through the polygonal outline of the shapes, curves being approximated by
+OUTLINE_SEGMENTS edges. Shapes that only touch do intersect, with an overlap area of 0.*/
+
+use std::f64::consts::PI;
+
+static OUTLINE_SEGMENTS: uint = 64;
+
+fn intersects(a: &Shape<f64>, b: &Shape<f64>) -> bool {
+    match (a, b) {
+        (&Circle(c1, r1), &Circle(c2, r2)) => {
+            let (dx, dy) = (c2.x - c1.x, c2.y - c1.y);
+            (dx * dx + dy * dy).sqrt() <= r1 + r2
+        },
+        (&Circle(c, r), &Rectangle(p1, p2)) | (&Rectangle(p1, p2), &Circle(c, r)) =>
+            rect_distance(p1, p2, c.x, c.y) <= r,
+        (&Rectangle(p1, p2), &Rectangle(q1, q2)) =>
+            overlap_length(p1.x, p2.x, q1.x, q2.x) >= 0.0 && overlap_length(p1.y, p2.y, q1.y, q2.y) >= 0.0,
+        _ => {
+            let (pa, pb) = (outline(a), outline(b));
+            let (na, nb) = (pa.len(), pb.len());
+            for i in range(0, na) {
+                for j in range(0, nb) {
+                    if segments_intersect(pa[i], pa[(i + 1) % na], pb[j], pb[(j + 1) % nb]) {
+                        return true;
+                    }
+                }
+            }
+            // No crossing edges, either one shape is inside the other or they are apart
+            (na > 0 && polygon_encloses(pb.as_slice(), pa[0].x, pa[0].y)) ||
+            (nb > 0 && polygon_encloses(pa.as_slice(), pb[0].x, pb[0].y))
+        }
+    }
+}
+
+fn overlap_area(a: &Shape<f64>, b: &Shape<f64>) -> f64 {
+    match (a, b) {
+        (&Circle(c1, r1), &Circle(c2, r2)) => {
+            let (dx, dy) = (c2.x - c1.x, c2.y - c1.y);
+            let d = (dx * dx + dy * dy).sqrt();
+            if d >= r1 + r2 {
+                0.0
+            } else if d <= (r1 - r2).abs() {
+                PI * r1.min(r2) * r1.min(r2)
+            } else {
+                // Two circular segments, one cut from each circle by the common chord
+                r1 * r1 * ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).acos()
+                    + r2 * r2 * ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).acos()
+                    - 0.5 * ((-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2)).sqrt()
+            }
+        },
+        (&Circle(c, r), &Rectangle(p1, p2)) | (&Rectangle(p1, p2), &Circle(c, r)) => {
+            // Inclusion-exclusion over the four corners of the rectangle, relative to the center
+            let (left, right) = (p1.x.min(p2.x) - c.x, p1.x.max(p2.x) - c.x);
+            let (top, bottom) = (p1.y.min(p2.y) - c.y, p1.y.max(p2.y) - c.y);
+            quadrant_area(r, right, bottom) - quadrant_area(r, left, bottom)
+                - quadrant_area(r, right, top) + quadrant_area(r, left, top)
+        },
+        (&Rectangle(p1, p2), &Rectangle(q1, q2)) =>
+            overlap_length(p1.x, p2.x, q1.x, q2.x).max(0.0) * overlap_length(p1.y, p2.y, q1.y, q2.y).max(0.0),
+        _ => {
+            // Fanning the second outline from its first vertex gives triangles whose signed sum is the
+            // shape itself, concave or not. Each triangle is convex, so it can clip the first outline.
+            let (subject, clip) = (outline(a), outline(b));
+            let mut area = 0.0;
+            for i in range(1, clip.len().max(2) - 1) {
+                area += clipped_area(subject.as_slice(), clip[0], clip[i], clip[i + 1]);
+            }
+            area.abs()
+        }
+    }
+}
+
+// Signed length of the overlap of [a1, a2] and [b1, b2], negative when they are apart
+fn overlap_length(a1: f64, a2: f64, b1: f64, b2: f64) -> f64 {
+    a1.max(a2).min(b1.max(b2)) - a1.min(a2).max(b1.min(b2))
+}
+
+// Integral of the half chord sqrt(r² - t²) of a circle from `lo` to min(`hi`, `x`)
+fn chord_integral(r: f64, lo: f64, hi: f64, x: f64) -> f64 {
+    let primitive = |t: f64| 0.5 * (t * (r * r - t * t).max(0.0).sqrt() + r * r * (t / r).max(-1.0).min(1.0).asin());
+    let hi = hi.min(x);
+    if hi <= lo { 0.0 } else { primitive(hi) - primitive(lo) }
+}
+
+// Area of the part of the circle of radius r centered on the origin lying left of x and below y
+fn quadrant_area(r: f64, x: f64, y: f64) -> f64 {
+    let x = x.max(-r).min(r);
+    if y <= -r {
+        return 0.0;
+    }
+    // Between -w and w the chords are cut by the horizontal line at y
+    let w = if y < r { (r * r - y * y).sqrt() } else { 0.0 };
+    let mut area = chord_integral(r, -w, w, x) + y * (w.min(x) + w).max(0.0);
+    if y > 0.0 {
+        // Outside of it the whole chord is below the line
+        area += 2.0 * (chord_integral(r, -r, -w, x) + chord_integral(r, w, r, x));
+    }
+    area
+}
+
+fn outline(sh: &Shape<f64>) -> Vec<Point<f64>> {
+    match *sh {
+        Circle(center, radius) => ellipse_outline(center, radius, radius),
+        Rectangle(p1, p2) => vec![p1, Point { x: p2.x, y: p1.y }, p2, Point { x: p1.x, y: p2.y }],
+        Polygon(ref points) => points.iter().map(|&p| p).collect(),
+        Ellipse(center, rx, ry) => ellipse_outline(center, rx, ry),
+        Triangle(a, b, c) => vec![a, b, c]
+    }
+}
+
+fn ellipse_outline(center: Point<f64>, rx: f64, ry: f64) -> Vec<Point<f64>> {
+    range(0, OUTLINE_SEGMENTS).map(|i| {
+        let angle = 2.0 * PI * i as f64 / OUTLINE_SEGMENTS as f64;
+        Point { x: center.x + rx * angle.cos(), y: center.y + ry * angle.sin() }
+    }).collect()
+}
+
+// orientation and segments_intersect are the generic ones from the data structures chapter, f64 being a Coordinate
+
+/*Sutherland-Hodgman clipping of `subject` by the triangle (a, b, c), returning the area of what is left.
+The result is signed like the triangle, so that the fan in overlap_area adds up.*/
+fn clipped_area(subject: &[Point<f64>], a: Point<f64>, b: Point<f64>, c: Point<f64>) -> f64 {
+    let sign = if orientation(a, b, c) < 0.0 { -1.0 } else { 1.0 };
+    // Walk the triangle counter-clockwise so that its inside is on the left of every edge
+    let triangle = if sign < 0.0 { [a, c, b] } else { [a, b, c] };
+    let mut clipped: Vec<Point<f64>> = subject.iter().map(|&p| p).collect();
+    for i in range(0u, 3) {
+        let (e1, e2) = (triangle[i], triangle[(i + 1) % 3]);
+        let input = clipped;
+        clipped = Vec::new();
+        let n = input.len();
+        for j in range(0, n) {
+            let (p, q) = (input[(j + n - 1) % n], input[j]);
+            let (side_p, side_q) = (orientation(e1, e2, p), orientation(e1, e2, q));
+            if (side_p >= 0.0) != (side_q >= 0.0) {
+                let t = side_p / (side_p - side_q);
+                clipped.push(Point { x: p.x + t * (q.x - p.x), y: p.y + t * (q.y - p.y) });
+            }
+            if side_q >= 0.0 {
+                clipped.push(q);
+            }
+        }
+    }
+    let n = clipped.len();
+    let mut twice_area = 0.0;
+    for i in range(0, n) {
+        let (p, q) = (clipped[i], clipped[(i + 1) % n]);
+        twice_area += p.x * q.y - q.x * p.y;
+    }
+    sign * (twice_area / 2.0).abs()
+}
+
+let left = Circle(Point { x: 0.0, y: 0.0 }, 1.0);
+let right = Circle(Point { x: 1.0, y: 0.0 }, 1.0);
+assert!(intersects(&left, &right));
+println!("overlap: {}", overlap_area(&left, &right));
+
+/*Static methods make good named constructors. An affine transform maps (x, y) to
+(a x + c y + e, b x + d y + f), and each kind of transform gets its own constructor.*/
+
+struct Transform {
+    a: f64, b: f64,
+    c: f64, d: f64,
+    e: f64, f: f64
+}
+
+impl Transform {
+    fn identity() -> Transform {
+        Transform { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 }
+    }
+
+    fn translate(dx: f64, dy: f64) -> Transform {
+        Transform { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: dx, f: dy }
+    }
+
+    // Around the origin, counter-clockwise when y points up
+    fn rotate(angle: f64) -> Transform {
+        let (sin, cos) = (angle.sin(), angle.cos());
+        Transform { a: cos, b: sin, c: -sin, d: cos, e: 0.0, f: 0.0 }
+    }
+
+    fn scale(sx: f64, sy: f64) -> Transform {
+        Transform { a: sx, b: 0.0, c: 0.0, d: sy, e: 0.0, f: 0.0 }
+    }
+
+    // x moves by kx times y, y moves by ky times x
+    fn shear(kx: f64, ky: f64) -> Transform {
+        Transform { a: 1.0, b: ky, c: kx, d: 1.0, e: 0.0, f: 0.0 }
+    }
+
+    // Applies `self`, then `next`
+    fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            e: next.a * self.e + next.c * self.f + next.e,
+            f: next.b * self.e + next.d * self.f + next.f
+        }
+    }
+
+    // A transform that flattens the plane onto a line cannot be undone
+    fn inverse(&self) -> Option<Transform> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 {
+            return None;
+        }
+        Some(Transform {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det
+        })
+    }
+
+    fn apply(&self, p: &Point<f64>) -> Point<f64> {
+        Point { x: self.a * p.x + self.c * p.y + self.e, y: self.b * p.x + self.d * p.y + self.f }
+    }
+
+    // Axis-aligned shapes stay axis-aligned
+    fn keeps_axes(&self) -> bool {
+        self.b == 0.0 && self.c == 0.0
+    }
+
+    // Rotation and uniform scaling, possibly mirrored: circles stay circles
+    fn is_similarity(&self) -> bool {
+        (self.a == self.d && self.b == -self.c) || (self.a == -self.d && self.b == self.c)
+    }
+}
+
+impl Shape<f64> {
+    /* Every variant maps to the variant that can hold the result. A rotated rectangle becomes a polygon
+    with the four rotated corners. The Ellipse variant is axis-aligned, so an ellipse that gets rotated or
+    sheared (or a circle that gets sheared) becomes a polygon following its outline. */
+    fn transform(&self, t: &Transform) -> Shape<f64> {
         match *self {
-            Circle(p, f) => draw_circle(p, f),
-            Rectangle(p1, p2) => draw_rectangle(p1, p2)
+            Circle(center, radius) if t.is_similarity() =>
+                Circle(t.apply(&center), radius * (t.a * t.a + t.b * t.b).sqrt()),
+            Circle(center, radius) if t.keeps_axes() =>
+                Ellipse(t.apply(&center), radius * t.a.abs(), radius * t.d.abs()),
+            Ellipse(center, rx, ry) if t.keeps_axes() =>
+                Ellipse(t.apply(&center), rx * t.a.abs(), ry * t.d.abs()),
+            Rectangle(p1, p2) if t.keeps_axes() =>
+                Rectangle(t.apply(&p1), t.apply(&p2)),
+            Triangle(a, b, c) =>
+                Triangle(t.apply(&a), t.apply(&b), t.apply(&c)),
+            _ => Polygon(outline(self).iter().map(|p| t.apply(p)).collect())
+        }
+    }
+}
+
+// Rotating a rectangle by 45 degrees around its center
+let rect = Rectangle(Point { x: 0.0, y: 0.0 }, Point { x: 4.0, y: 2.0 });
+let around_center = Transform::translate(-2.0, -1.0)
+    .then(&Transform::rotate(PI / 4.0))
+    .then(&Transform::translate(2.0, 1.0));
+let rotated = rect.transform(&around_center); // a Polygon
+let back = rotated.transform(&around_center.inverse().unwrap());
+
+/*Curves that none of the variants can describe are built as a path, the way SVG and PostScript do it: a list
+of drawing commands, each one continuing from where the previous one ended. Consuming `self` and handing it
+back lets the builder methods be chained. The type is called BezierPath so as not to hide std::path::Path, needed
+to save the images further down.*/
+
+enum PathCommand {
+    MoveTo(Point<f64>),
+    LineTo(Point<f64>),
+    QuadTo(Point<f64>, Point<f64>),          // control point, end
+    CubicTo(Point<f64>, Point<f64>, Point<f64>),  // two control points, end
+    Close
+}
+
+struct BezierPath {
+    commands: Vec<PathCommand>
+}
+
+struct Polyline {
+    points: Vec<Point<f64>>,
+    closed: bool
+}
+
+impl BezierPath {
+    fn new() -> BezierPath { BezierPath { commands: Vec::new() } }
+
+    fn move_to(mut self, p: Point<f64>) -> BezierPath { self.commands.push(MoveTo(p)); self }
+    fn line_to(mut self, p: Point<f64>) -> BezierPath { self.commands.push(LineTo(p)); self }
+    fn quad_to(mut self, control: Point<f64>, p: Point<f64>) -> BezierPath { self.commands.push(QuadTo(control, p)); self }
+    fn cubic_to(mut self, c1: Point<f64>, c2: Point<f64>, p: Point<f64>) -> BezierPath { self.commands.push(CubicTo(c1, c2, p)); self }
+    fn close(mut self) -> BezierPath { self.commands.push(Close); self }
+
+    /* Turns the path into polylines, one per subpath, that never stray further than `tolerance` from the
+    curves. A Bezier curve is cut into n pieces of equal parameter length, where n comes from the bound on
+    the distance between a curve and its chord: an eighth of the largest second derivative times the
+    square of the piece length. */
+    fn flatten(&self, tolerance: f64) -> Vec<Polyline> {
+        let mut lines = Vec::new();
+        let mut current: Vec<Point<f64>> = Vec::new();
+        for command in self.commands.iter() {
+            match *command {
+                MoveTo(p) => {
+                    let previous = replace(&mut current, vec![p]);
+                    push_polyline(&mut lines, previous, false);
+                },
+                LineTo(p) => {
+                    pen(&mut current);
+                    current.push(p);
+                },
+                QuadTo(c, p) => {
+                    let start = pen(&mut current);
+                    let bend = length(start.x - 2.0 * c.x + p.x, start.y - 2.0 * c.y + p.y);
+                    let n = pieces(2.0 * bend, tolerance);
+                    for i in range(1, n + 1) {
+                        let t = i as f64 / n as f64;
+                        let (u, v, w) = ((1.0 - t) * (1.0 - t), 2.0 * t * (1.0 - t), t * t);
+                        current.push(Point { x: u * start.x + v * c.x + w * p.x, y: u * start.y + v * c.y + w * p.y });
+                    }
+                },
+                CubicTo(c1, c2, p) => {
+                    let start = pen(&mut current);
+                    let bend = length(start.x - 2.0 * c1.x + c2.x, start.y - 2.0 * c1.y + c2.y)
+                        .max(length(c1.x - 2.0 * c2.x + p.x, c1.y - 2.0 * c2.y + p.y));
+                    let n = pieces(6.0 * bend, tolerance);
+                    for i in range(1, n + 1) {
+                        let t = i as f64 / n as f64;
+                        let s = 1.0 - t;
+                        let (a, b, c, d) = (s * s * s, 3.0 * s * s * t, 3.0 * s * t * t, t * t * t);
+                        current.push(Point { x: a * start.x + b * c1.x + c * c2.x + d * p.x,
+                                             y: a * start.y + b * c1.y + c * c2.y + d * p.y });
+                    }
+                },
+                Close => {
+                    // The next command starts back from the beginning of the closed subpath
+                    let start = pen(&mut current);
+                    let first = if current.is_empty() { start } else { current[0] };
+                    let closed = replace(&mut current, vec![first]);
+                    push_polyline(&mut lines, closed, true);
+                }
+            }
+        }
+        push_polyline(&mut lines, current, false);
+        lines
+    }
+
+    fn arc_length(&self, tolerance: f64) -> f64 {
+        let mut total = 0.0;
+        for line in self.flatten(tolerance).iter() {
+            let points = line.points.as_slice();
+            for i in range(1, points.len()) {
+                total += length(points[i].x - points[i - 1].x, points[i].y - points[i - 1].y);
+            }
+            if line.closed {
+                let (first, last) = (points[0], points[points.len() - 1]);
+                total += length(first.x - last.x, first.y - last.y);
+            }
+        }
+        total
+    }
+
+    /* Only closed paths have an area. Subpaths going the other way round count negatively, which is
+    how holes are usually drawn. */
+    fn area(&self, tolerance: f64) -> Option<f64> {
+        let lines = self.flatten(tolerance);
+        if lines.iter().any(|line| !line.closed) {
+            return None;
+        }
+        let mut twice_area = 0.0;
+        for line in lines.iter() {
+            let (points, n) = (line.points.as_slice(), line.points.len());
+            for i in range(0, n) {
+                let (p, q) = (points[i], points[(i + 1) % n]);
+                twice_area += p.x * q.y - q.x * p.y;
+            }
+        }
+        Some((twice_area / 2.0).abs())
+    }
+
+    fn draw(&self, canvas: &mut Canvas, tolerance: f64) {
+        for line in self.flatten(tolerance).iter() {
+            if line.closed { canvas.polygon(line.points.as_slice()) } else { canvas.polyline(line.points.as_slice()) }
+        }
+    }
+}
+
+use std::mem::replace;
+
+// Where the pen is, a path that does not start with a move starts from the origin
+fn pen(current: &mut Vec<Point<f64>>) -> Point<f64> {
+    if current.is_empty() {
+        current.push(Point { x: 0.0, y: 0.0 });
+    }
+    current[current.len() - 1]
+}
+
+fn push_polyline(lines: &mut Vec<Polyline>, points: Vec<Point<f64>>, closed: bool) {
+    if points.len() >= 2 {
+        lines.push(Polyline { points: points, closed: closed });
+    }
+}
+
+fn length(dx: f64, dy: f64) -> f64 { (dx * dx + dy * dy).sqrt() }
+
+// Number of pieces keeping a curve whose second derivative never exceeds `second` within `tolerance`
+fn pieces(second: f64, tolerance: f64) -> uint {
+    ((second / (8.0 * tolerance)).sqrt().ceil() as uint).max(1)
+}
+
+let drop = BezierPath::new()
+    .move_to(Point { x: 50.0, y: 0.0 })
+    .cubic_to(Point { x: 50.0, y: 40.0 }, Point { x: 90.0, y: 60.0 }, Point { x: 50.0, y: 100.0 })
+    .cubic_to(Point { x: 10.0, y: 60.0 }, Point { x: 50.0, y: 40.0 }, Point { x: 50.0, y: 0.0 })
+    .close();
+println!("length: {}, area: {}", drop.arc_length(0.1), drop.area(0.1).unwrap());
+
+// Solids get the same kind of methods, with Point3 and compute_distance3 from the references chapter
+
+struct Sphere {
+    center: Point3<f64>,
+    radius: f64
+}
+
+// Axis-aligned box, `min` and `max` being opposite corners
+struct Box3 {
+    min: Point3<f64>,
+    max: Point3<f64>
+}
+
+impl Sphere {
+    fn volume(&self) -> f64 { 4.0 / 3.0 * PI * self.radius * self.radius * self.radius }
+    fn surface_area(&self) -> f64 { 4.0 * PI * self.radius * self.radius }
+
+    // Distance from `p` to the surface, negative inside
+    fn distance(&self, p: &Point3<f64>) -> f64 { compute_distance3(&self.center, p) - self.radius }
+}
+
+impl Box3 {
+    // The corners can be given in any order
+    fn new(p1: Point3<f64>, p2: Point3<f64>) -> Box3 {
+        Box3 { min: Point3 { x: p1.x.min(p2.x), y: p1.y.min(p2.y), z: p1.z.min(p2.z) },
+               max: Point3 { x: p1.x.max(p2.x), y: p1.y.max(p2.y), z: p1.z.max(p2.z) } }
+    }
+
+    fn size(&self) -> (f64, f64, f64) {
+        (self.max.x - self.min.x, self.max.y - self.min.y, self.max.z - self.min.z)
+    }
+
+    fn volume(&self) -> f64 {
+        let (w, h, d) = self.size();
+        w * h * d
+    }
+
+    fn surface_area(&self) -> f64 {
+        let (w, h, d) = self.size();
+        2.0 * (w * h + h * d + d * w)
+    }
+
+    // Distance from `p` to the surface, negative inside like for the sphere
+    fn distance(&self, p: &Point3<f64>) -> f64 {
+        let closest = Point3 { x: p.x.max(self.min.x).min(self.max.x),
+                               y: p.y.max(self.min.y).min(self.max.y),
+                               z: p.z.max(self.min.z).min(self.max.z) };
+        let outside = compute_distance3(&closest, p);
+        if outside > 0.0 {
+            return outside;
         }
+        // Inside, the nearest point of the surface is straight across the nearest face
+        let depth = (p.x - self.min.x).min(self.max.x - p.x)
+                        .min(p.y - self.min.y).min(self.max.y - p.y)
+                        .min(p.z - self.min.z).min(self.max.z - p.z);
+        -depth
     }
 }
 
+let ball = Sphere { center: Point3 { x: 0.0, y: 0.0, z: 0.0 }, radius: 1.0 };
+let container = Box3::new(Point3 { x: 1.0, y: 1.0, z: 1.0 }, Point3 { x: -1.0, y: 0.0, z: 2.0 });
+println!("{} {} {}", ball.volume(), container.surface_area(), container.distance(&ball.center));
+assert!(container.distance(&Point3 { x: 0.5, y: 0.5, z: 1.5 }) == -0.5);
+
 let s = Circle(Point { x: 1.0, y: 2.0 }, 3.0);
-s.draw();
+let mut svg = Svg::new(100.0, 100.0);
+s.draw(&mut svg);
+println!("{}", svg.to_string());
+
+// The same shape, straight in the terminal
+let mut ascii = AsciiCanvas::new(40, 20, 0.25, 2.0);
+s.draw(&mut ascii);
+ascii.print();
+
+// Or as an image, one unit being one pixel
+use std::io::File;
+let mut raster = Raster::new(200, 200);
+raster.color = (200, 0, 0);
+Rectangle(Point { x: 20.0, y: 20.0 }, Point { x: 180.0, y: 120.0 }).draw(&mut raster);
+Circle(Point { x: 100.0, y: 100.0 }, 60.0).draw(&mut raster);
+Ellipse(Point { x: 100.0, y: 150.0 }, 80.0, 30.0).draw(&mut raster);
+Triangle(Point { x: 20.0, y: 180.0 }, Point { x: 100.0, y: 20.0 }, Point { x: 180.0, y: 180.0 }).draw(&mut raster);
+File::create(&Path::new("shapes.ppm")).write(raster.to_ppm().as_slice()).unwrap();
+File::create(&Path::new("shapes.png")).write(raster.to_png().as_slice()).unwrap();
 
 /*This defines an implementation for Shape containing a single method, draw. 
 In most respects the draw method is defined like any other function, except for the name self.*/
@@ -34,9 +918,9 @@ In most respects the draw method is defined like any other function, except for
 As an argument it is written either self, &self, or self: TYPE. A caller must in turn 
 have a compatible pointer type to call the method.*/
 
-impl Shape {
+impl Shape<f64> {
     fn draw_reference(&self) { /* ... */ }
-    fn draw_owned(self: Box<Shape>) { /* ... */ }
+    fn draw_owned(self: Box<Shape<f64>>) { /* ... */ }
     fn draw_value(self) { /* ... */ }
 }
 
@@ -80,4 +964,31 @@ struct Circle { radius: f64 }
 impl Circle {
     fn new(area: f64) -> Circle { Circle { radius: (area / PI).sqrt() } }
 }
-let c = Circle::new(42.5);
\ No newline at end of file
+let c = Circle::new(42.5);
+
+/*`new` trusts its argument: a negative or NaN area gives a NaN radius, which then ends up in every 
+computation made with the circle. Constructors that can fail return a Result instead, with the GeometryError 
+and the `measure` check from the data structures chapter.*/
+
+impl Circle {
+    fn try_new(area: f64) -> Result<Circle, GeometryError> {
+        let area = try!(measure(area));
+        Ok(Circle { radius: (area / PI).sqrt() })
+    }
+
+    fn from_perimeter(perimeter: f64) -> Result<Circle, GeometryError> {
+        let perimeter = try!(measure(perimeter));
+        Ok(Circle { radius: perimeter / (2.0 * PI) })
+    }
+
+    // The longest chord of a circle is its diameter
+    fn from_diagonal(diagonal: f64) -> Result<Circle, GeometryError> {
+        let diagonal = try!(measure(diagonal));
+        Ok(Circle { radius: diagonal / 2.0 })
+    }
+}
+
+match Circle::try_new(-1.0) {
+    Ok(c) => println!("radius: {}", c.radius),
+    Err(e) => println!("no circle: {}", e)
+}
\ No newline at end of file
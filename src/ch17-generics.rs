@@ -40,10 +40,13 @@ null pointers (except in unsafe code), we need another way to write a function w
 defined on every possible combination of arguments of the appropriate types. The usual way is to write 
 a function that returns Option<T> instead of T.*/
 
-fn radius(shape: Shape) -> Option<f64> {
+fn radius(shape: Shape<f64>) -> Option<f64> {
     match shape {
         Circle(_, radius) => Some(radius),
-        Rectangle(..)     => None
+        Rectangle(..)     => None,
+        Polygon(..)       => None,
+        Ellipse(..)       => None,
+        Triangle(..)      => None
     }
 }
 
@@ -231,6 +234,40 @@ let area = 42.5;
 let c: Circle = Shape::new(area);
 let s: Square = Shape::new(area);
 
+/* `new` takes the square root of whatever it is given, so a negative or NaN area gives a NaN size. The fallible 
+constructors check their argument with `measure` from the data structures chapter and return a GeometryError 
+instead. `try_new` is a default method: it only needs `new`, so every implementation gets it for free.*/
+
+trait FallibleShape : Shape {
+    fn try_new(area: f64) -> Result<Self, GeometryError> {
+        measure(area).map(|area| Shape::new(area))
+    }
+    fn from_perimeter(perimeter: f64) -> Result<Self, GeometryError>;
+    fn from_diagonal(diagonal: f64) -> Result<Self, GeometryError>;
+}
+
+impl FallibleShape for Circle {
+    fn from_perimeter(perimeter: f64) -> Result<Circle, GeometryError> {
+        measure(perimeter).map(|perimeter| Circle { radius: perimeter / (2.0 * PI) })
+    }
+    // The longest chord of a circle is its diameter
+    fn from_diagonal(diagonal: f64) -> Result<Circle, GeometryError> {
+        measure(diagonal).map(|diagonal| Circle { radius: diagonal / 2.0 })
+    }
+}
+impl FallibleShape for Square {
+    fn from_perimeter(perimeter: f64) -> Result<Square, GeometryError> {
+        measure(perimeter).map(|perimeter| Square { length: perimeter / 4.0 })
+    }
+    fn from_diagonal(diagonal: f64) -> Result<Square, GeometryError> {
+        measure(diagonal).map(|diagonal| Square { length: diagonal / 2.0f64.sqrt() })
+    }
+}
+
+let c: Circle = FallibleShape::try_new(area).unwrap();
+let s: Result<Square, GeometryError> = FallibleShape::from_diagonal(-2.0);
+assert!(s.is_err());
+
 // -- Bounded type parameters and static method dispatch --
 
 /* Traits give a language for defining predicates on types, or abstract properties that types can have. We can use this language to define bounds 
@@ -265,30 +302,51 @@ way to use traits polymorphically.*/
 /*The above allows us to define functions that polymorphically act on values of a single unknown type that conforms to a given trait. 
 However, consider this function:*/
 
-trait Drawable { fn draw(&self); }
+trait Drawable { fn draw(&self, canvas: &mut Canvas); }
 
-fn draw_all<T: Drawable>(shapes: Vec<T>) {
-    for shape in shapes.iter() { shape.draw(); }
+fn draw_all<T: Drawable>(shapes: Vec<T>, canvas: &mut Canvas) {
+    for shape in shapes.iter() { shape.draw(canvas); }
 }
 
 /*You can call that on a vector of circles, or a vector of rectangles (assuming those have suitable Drawable traits defined), 
 but not on a vector containing both circles and rectangles. When such behavior is needed, 
 a trait name can alternately be used as a type, called an object.*/
 
-fn draw_all(shapes: &[Box<Drawable>]) {
-    for shape in shapes.iter() { shape.draw(); }
+fn draw_all(shapes: &[Box<Drawable>], canvas: &mut Canvas) {
+    for shape in shapes.iter() { shape.draw(canvas); }
 }
 
 /*In this example, there is no type parameter. Instead, the Box<Drawable> type denotes any owned box value that implements the Drawable trait. 
 To construct such a value, you use the as operator to cast a value to an object:*/
 
 
-impl Drawable for Circle { fn draw(&self) { /* ... */ } }
-impl Drawable for Rectangle { fn draw(&self) { /* ... */ } }
+struct Circle { center: Point<f64>, radius: f64 }
+struct Rectangle { corner: Point<f64>, opposite: Point<f64> }
+
+// Canvas, Svg and AsciiCanvas being the ones from the methods chapter
+impl Drawable for Circle { fn draw(&self, canvas: &mut Canvas) { canvas.circle(self.center, self.radius) } }
+impl Drawable for Rectangle { fn draw(&self, canvas: &mut Canvas) { canvas.rect(self.corner, self.opposite) } }
+// The Shape enum already has a draw method of its own, which method calls pick before the trait's
+impl Drawable for Shape<f64> { fn draw(&self, canvas: &mut Canvas) { self.draw(canvas) } }
+// Paths are flattened to a quarter of a unit, a quarter of a pixel on a Raster
+impl Drawable for BezierPath { fn draw(&self, canvas: &mut Canvas) { self.draw(canvas, 0.25) } }
 
 let c: Box<Circle> = box new_circle();
 let r: Box<Rectangle> = box new_rectangle();
-draw_all([c as Box<Drawable>, r as Box<Drawable>]);
+let scene = [c as Box<Drawable>, r as Box<Drawable>];
+
+// The whole scene can then be saved as an image
+let mut svg = Svg::new(200.0, 200.0);
+draw_all(scene, &mut svg);
+
+use std::io::File;
+let mut file = File::create(&Path::new("scene.svg"));
+file.write_str(svg.to_string().as_slice()).unwrap();
+
+// or printed in the terminal
+let mut ascii = AsciiCanvas::new(80, 40, 2.5, 2.0);
+draw_all(scene, &mut ascii);
+ascii.print();
 
 /*Note that, like strings and vectors, objects have dynamic size and may only be referred to via one of the pointer types. Other pointer types work as well. 
 Casts to traits may only be done with compatible pointers so, for example, an &Circle may not be cast to a Box<Drawable>.*/
@@ -326,18 +384,43 @@ for traits of the same storage class.*/
 /*We can write a trait declaration that inherits from other traits, called supertraits. Types that implement a trait must also implement its supertraits. 
 For example, we can define a Circle trait that inherits from Shape.*/
 
-trait Shape { fn area(&self) -> f64; }
+trait Shape {
+    fn area(&self) -> f64;
+    fn perimeter(&self) -> f64;
+    fn centroid(&self) -> Point<f64>;
+    // About the axes through the centroid, see SecondMoment in the data structures chapter
+    fn second_moment(&self) -> SecondMoment;
+    // Negative inside, positive outside
+    fn signed_distance(&self, p: &Point<f64>) -> f64;
+
+    // Boundary being the one from the methods chapter
+    fn contains_with(&self, p: &Point<f64>, boundary: Boundary, epsilon: f64) -> bool {
+        let distance = self.signed_distance(p);
+        match boundary {
+            Inclusive => distance <= epsilon,
+            Exclusive => distance < -epsilon
+        }
+    }
+    fn contains(&self, p: &Point<f64>) -> bool { self.contains_with(p, Inclusive, 0.0) }
+}
 trait Circle : Shape { fn radius(&self) -> f64; }
 
 // Now, we can implement Circle on a type only if we also implement Shape.
 
 use std::f64::consts::PI;
-struct CircleStruct { center: Point, radius: f64 }
+struct CircleStruct { center: Point<f64>, radius: f64 }
 impl Circle for CircleStruct {
     fn radius(&self) -> f64 { (self.area() / PI).sqrt() }
 }
 impl Shape for CircleStruct {
     fn area(&self) -> f64 { PI * square(self.radius) }
+    fn perimeter(&self) -> f64 { 2.0 * PI * self.radius }
+    fn centroid(&self) -> Point<f64> { self.center }
+    fn second_moment(&self) -> SecondMoment {
+        let i = PI * square(square(self.radius)) / 4.0;
+        SecondMoment { ix: i, iy: i, ixy: 0.0 }
+    }
+    fn signed_distance(&self, p: &Point<f64>) -> f64 { compute_distance(&self.center, p) - self.radius }
 }
 
 /*Notice that methods of Circle can call methods on Shape, as our radius implementation calls the area method.
@@ -357,6 +440,83 @@ let concrete = box CircleStruct{center:Point{x:3.0,y:4.0},radius:5.0};
 let mycircle: Box<Circle> = concrete as Box<Circle>;
 let nonsense = mycircle.radius() * mycircle.area();
 
+// -- Bounding boxes --
+
+/*A trait can also give the same query to otherwise unrelated types. Whatever its representation, every shape 
+can tell the smallest axis-aligned rectangle containing it, which is the first question asked by spatial indexes 
+and renderers. That box gets a type of its own, Aabb (axis-aligned bounding box), so that it is never mistaken for 
+the Rectangle struct or the Rectangle variant of Shape: `min` and `max` hold the minimum and maximum of both coordinates.*/
+
+struct Aabb { min: Point<f64>, max: Point<f64> }
+
+trait Bounded { fn bounds(&self) -> Aabb; }
+
+impl Aabb {
+    fn from_corners(p1: Point<f64>, p2: Point<f64>) -> Aabb {
+        Aabb { min: Point { x: p1.x.min(p2.x), y: p1.y.min(p2.y) },
+               max: Point { x: p1.x.max(p2.x), y: p1.y.max(p2.y) } }
+    }
+
+    // Contains nothing, and is the neutral element of union
+    fn empty() -> Aabb {
+        Aabb { min: Point { x: INFINITY, y: INFINITY },
+               max: Point { x: NEG_INFINITY, y: NEG_INFINITY } }
+    }
+
+    fn union(&self, other: &Aabb) -> Aabb {
+        Aabb { min: Point { x: self.min.x.min(other.min.x), y: self.min.y.min(other.min.y) },
+               max: Point { x: self.max.x.max(other.max.x), y: self.max.y.max(other.max.y) } }
+    }
+
+    // Grows the box just enough to contain `p`
+    fn expand_to(&self, p: &Point<f64>) -> Aabb {
+        self.union(&Aabb { min: *p, max: *p })
+    }
+
+    // Grows the box by `margin` on every side
+    fn expand(&self, margin: f64) -> Aabb {
+        Aabb { min: Point { x: self.min.x - margin, y: self.min.y - margin },
+               max: Point { x: self.max.x + margin, y: self.max.y + margin } }
+    }
+}
+
+use std::f64::{INFINITY, NEG_INFINITY};
+
+fn points_bounds(points: &[Point<f64>]) -> Aabb {
+    points.iter().fold(Aabb::empty(), |bounds, p| bounds.expand_to(p))
+}
+
+fn circle_bounds(center: Point<f64>, rx: f64, ry: f64) -> Aabb {
+    Aabb { min: Point { x: center.x - rx, y: center.y - ry },
+           max: Point { x: center.x + rx, y: center.y + ry } }
+}
+
+impl Bounded for Rectangle {
+    fn bounds(&self) -> Aabb { Aabb::from_corners(self.corner, self.opposite) }
+}
+
+impl Bounded for CircleStruct {
+    fn bounds(&self) -> Aabb { circle_bounds(self.center, self.radius, self.radius) }
+}
+
+// The Shape enum, as in the methods chapter
+impl Bounded for Shape<f64> {
+    fn bounds(&self) -> Aabb {
+        match *self {
+            Circle(center, radius)  => circle_bounds(center, radius, radius),
+            Rectangle(p1, p2)       => Aabb::from_corners(p1, p2),
+            Polygon(ref points)     => points_bounds(points.as_slice()),
+            Ellipse(center, rx, ry) => circle_bounds(center, rx, ry),
+            Triangle(a, b, c)       => points_bounds([a, b, c])
+        }
+    }
+}
+
+// A whole scene is bounded by the union of the bounds of its shapes
+fn scene_bounds<T: Bounded>(shapes: &[T]) -> Aabb {
+    shapes.iter().fold(Aabb::empty(), |bounds, shape| bounds.union(&shape.bounds()))
+}
+
 // -- Deriving implementations for traits --
 
 /*A small number of traits in can have implementations that can be automatically derived. These instances are specified by placing the deriving 
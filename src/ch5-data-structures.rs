@@ -1,11 +1,39 @@
+extern crate num;
+
+use std::cmp::{Ordering, Equal};
 use std::f64;
+use std::num::Zero;
+use num::rational::Ratio;
+
+/* The error of every geometric constructor that can fail, here and in the later chapters. It lives
+ * outside of `main`, like `measure` which checks lengths and areas, so that it is shared by all of them.
+ */
+#[deriving(Show, PartialEq)]
+enum GeometryError {
+    NotANumber,
+    Infinite,
+    Negative
+}
+
+// Lengths and areas must be finite numbers, and not negative
+fn measure(v: f64) -> Result<f64, GeometryError> {
+    if v.is_nan() {
+        Err(NotANumber)
+    } else if v.is_infinite() {
+        Err(Infinite)
+    } else if v < 0.0 {
+        Err(Negative)
+    } else {
+        Ok(v)
+    }
+}
 
 fn main() {
-	// Struct
+	// Struct, generic over the type of its coordinates: Point<f64>, Point<i64>...
 
-	struct Point {
-	    x: f64,
-	    y: f64
+	struct Point<T> {
+	    x: T,
+	    y: T
 	}
 
 	// Inherited mutability
@@ -25,6 +53,30 @@ fn main() {
 	    Point { x, .. } => println!("{}", x)
 	}
 
+	/* Geometry only needs a few operations on coordinates, gathered in a trait so that
+	 * integer grids and exact rationals work as well as floats. What cannot be exact,
+	 * like lengths or anything involving a circle, is computed in f64.
+	 */
+	trait Coordinate : Num + PartialOrd + Copy {
+	    fn to_f64(&self) -> f64;
+	}
+
+	impl Coordinate for i32 { fn to_f64(&self) -> f64 { *self as f64 } }
+	impl Coordinate for i64 { fn to_f64(&self) -> f64 { *self as f64 } }
+	impl Coordinate for f32 { fn to_f64(&self) -> f64 { *self as f64 } }
+	impl Coordinate for f64 { fn to_f64(&self) -> f64 { *self } }
+	impl Coordinate for Ratio<i64> {
+	    fn to_f64(&self) -> f64 { *self.numer() as f64 / *self.denom() as f64 }
+	}
+
+	fn abs<T: Coordinate>(v: T) -> T {
+	    let zero: T = Zero::zero();
+	    if v < zero { zero - v } else { v }
+	}
+
+	let exact = Point { x: Ratio::new(1i64, 3), y: Ratio::new(2i64, 3) };
+	let on_grid = Point { x: 3i64, y: -2 };
+
 	// Enum
 
 	enum Direction {
@@ -45,13 +97,16 @@ fn main() {
 	}
 
 	// Variants can be of a more complex type 
-	enum Shape {
-	    Circle(Point, f64),
-	    Rectangle(Point, Point)
+	enum Shape<T> {
+	    Circle(Point<T>, T),
+	    Rectangle(Point<T>, Point<T>),
+	    Polygon(Vec<Point<T>>),
+	    Ellipse(Point<T>, T, T),
+	    Triangle(Point<T>, Point<T>, Point<T>)
 	}
-	/* A value of this type is either a Circle or a Rectangle
+	/* A value of this type is either a Circle, a Rectangle, a Polygon, an Ellipse or a Triangle
 	 * This declaration defines a type Shape that can refer to such shapes, 
-	 * and two functions, Circle and Rectangle, which can be used to 
+	 * and one function per variant, Circle, Rectangle and so on, which can be used to 
 	 * construct values of the type.
 	 */
 
@@ -60,17 +115,285 @@ fn main() {
 
 	// All of these variants' constructor can be used as pattern
 
-	fn area(sh: Shape) -> f64 {
+	fn area<T: Coordinate>(sh: Shape<T>) -> f64 {
 	    match sh {
-	        Circle(_, size) => f64::consts::PI * size * size,
-	        Rectangle(Point { x, y }, Point { x: x2, y: y2 }) => (x2 - x) * (y2 - y)
+	        Circle(_, size) => f64::consts::PI * size.to_f64() * size.to_f64(),
+	        Rectangle(Point { x, y }, Point { x: x2, y: y2 }) => abs((x2 - x) * (y2 - y)).to_f64(),
+	        Polygon(points) => signed_area(points.as_slice()).abs(),
+	        Ellipse(_, rx, ry) => f64::consts::PI * rx.to_f64() * ry.to_f64(),
+	        Triangle(a, b, c) => abs(orientation(a, b, c)).to_f64() / 2.0
 	    }
 	}
 
 	let rect = Rectangle(Point { x: 0.0, y: 0.0 }, Point { x: 2.0, y: 2.0 });
 	println!("area: {}", area(rect));
 
-	fn point_from_direction(dir: Direction) -> Point {
+	/* Nothing stops a Rectangle from being built with its corners swapped, or with a NaN
+	 * somewhere that turns every later computation into garbage. Going through `rectangle`
+	 * checks the coordinates and puts the lowest one first on each axis, failing with the
+	 * GeometryError defined above `main`.
+	 */
+	fn check<T: Coordinate>(v: T) -> Result<T, GeometryError> {
+	    if v != v {
+	        Err(NotANumber) // NaN is the only value not equal to itself
+	    } else if v.to_f64().is_infinite() {
+	        Err(Infinite)
+	    } else {
+	        Ok(v)
+	    }
+	}
+
+	fn rectangle<T: Coordinate>(a: Point<T>, b: Point<T>) -> Result<Shape<T>, GeometryError> {
+	    let (x1, y1, x2, y2) = (try!(check(a.x)), try!(check(a.y)), try!(check(b.x)), try!(check(b.y)));
+	    let (min_x, max_x) = if x1 <= x2 { (x1, x2) } else { (x2, x1) };
+	    let (min_y, max_y) = if y1 <= y2 { (y1, y2) } else { (y2, y1) };
+	    Ok(Rectangle(Point { x: min_x, y: min_y }, Point { x: max_x, y: max_y }))
+	}
+
+	// Extent of a shape along each axis, never negative whichever way it was built
+	fn width<T: Coordinate>(sh: &Shape<T>) -> T {
+	    match *sh {
+	        Circle(_, r) => abs(r + r),
+	        Ellipse(_, rx, _) => abs(rx + rx),
+	        Rectangle(p1, p2) => abs(p2.x - p1.x),
+	        Polygon(ref points) => spread(points.iter().map(|p| p.x)),
+	        Triangle(a, b, c) => spread(vec![a.x, b.x, c.x].move_iter())
+	    }
+	}
+
+	fn height<T: Coordinate>(sh: &Shape<T>) -> T {
+	    match *sh {
+	        Circle(_, r) => abs(r + r),
+	        Ellipse(_, _, ry) => abs(ry + ry),
+	        Rectangle(p1, p2) => abs(p2.y - p1.y),
+	        Polygon(ref points) => spread(points.iter().map(|p| p.y)),
+	        Triangle(a, b, c) => spread(vec![a.y, b.y, c.y].move_iter())
+	    }
+	}
+
+	// Largest value minus smallest one, zero when there are none
+	fn spread<T: Coordinate, I: Iterator<T>>(mut values: I) -> T {
+	    let first = match values.next() { Some(v) => v, None => return Zero::zero() };
+	    let (lo, hi) = values.fold((first, first), |(lo, hi), v| {
+	        (if v < lo { v } else { lo }, if v > hi { v } else { hi })
+	    });
+	    hi - lo
+	}
+
+	let swapped = rectangle(Point { x: 2.0, y: 2.0 }, Point { x: 0.0, y: 0.0 }).unwrap();
+	assert!(width(&swapped) == 2.0 && height(&swapped) == 2.0);
+	assert!(rectangle(Point { x: 0.0, y: f64::NAN }, Point { x: 1.0, y: 1.0 }).is_err());
+
+	// Twice the area of the shapes with straight edges, which stays exact on an integer grid
+	fn twice_area<T: Coordinate>(sh: &Shape<T>) -> Option<T> {
+	    match *sh {
+	        Rectangle(p1, p2) => {
+	            let area = (p2.x - p1.x) * (p2.y - p1.y);
+	            Some(abs(area + area))
+	        },
+	        Polygon(ref points) => Some(abs(twice_signed_area(points.as_slice()))),
+	        Triangle(a, b, c) => Some(abs(orientation(a, b, c))),
+	        Circle(..) | Ellipse(..) => None
+	    }
+	}
+
+	let plot = Triangle(Point { x: 0i64, y: 0 }, Point { x: 3, y: 0 }, Point { x: 0, y: 1 });
+	assert!(twice_area(&plot) == Some(3)); // no rounding involved
+
+	/* The area of a polygon comes from the shoelace formula. The sum is signed: positive when
+	 * the vertices go counter-clockwise (with y pointing up), negative when they go clockwise.
+	 * For a self-intersecting polygon the lobes cancel each other out, so the result only
+	 * means something for simple polygons.
+	 */
+	fn twice_signed_area<T: Coordinate>(points: &[Point<T>]) -> T {
+	    let n = points.len();
+	    let mut sum: T = Zero::zero();
+	    for i in range(0, n) {
+	        let (p, q) = (points[i], points[(i + 1) % n]);
+	        sum = sum + p.x * q.y - q.x * p.y;
+	    }
+	    sum
+	}
+
+	fn signed_area<T: Coordinate>(points: &[Point<T>]) -> f64 {
+	    twice_signed_area(points).to_f64() / 2.0
+	}
+
+	enum Winding {
+	    Clockwise,
+	    CounterClockwise
+	}
+
+	fn winding<T: Coordinate>(points: &[Point<T>]) -> Winding {
+	    if twice_signed_area(points) < Zero::zero() { Clockwise } else { CounterClockwise }
+	}
+
+	// Puts the vertices in counter-clockwise order, which most algorithms expect
+	fn counter_clockwise<T: Coordinate>(mut points: Vec<Point<T>>) -> Vec<Point<T>> {
+	    match winding(points.as_slice()) {
+	        Clockwise => points.reverse(),
+	        CounterClockwise => ()
+	    }
+	    points
+	}
+
+	// Positive if `c` is on the left of the line going from `a` to `b`, 0 if the three are aligned
+	fn orientation<T: Coordinate>(a: Point<T>, b: Point<T>, c: Point<T>) -> T {
+	    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+	}
+
+	// Left to right, and bottom to top along a vertical: the order in which hulls and sweeps visit points
+	fn lexicographic<T: Coordinate>(a: &Point<T>, b: &Point<T>) -> Ordering {
+	    match a.x.partial_cmp(&b.x) {
+	        Some(Equal) | None => a.y.partial_cmp(&b.y).unwrap_or(Equal),
+	        Some(order) => order
+	    }
+	}
+
+	// Whether `p`, known to be aligned with `a` and `b`, lies between them
+	fn on_segment<T: Coordinate>(a: Point<T>, b: Point<T>, p: Point<T>) -> bool {
+	    let between = |lo: T, hi: T, v: T| (lo <= v && v <= hi) || (hi <= v && v <= lo);
+	    between(a.x, b.x, p.x) && between(a.y, b.y, p.y)
+	}
+
+	fn segments_intersect<T: Coordinate>(a: Point<T>, b: Point<T>, c: Point<T>, d: Point<T>) -> bool {
+	    let zero: T = Zero::zero();
+	    let (d1, d2) = (orientation(c, d, a), orientation(c, d, b));
+	    let (d3, d4) = (orientation(a, b, c), orientation(a, b, d));
+	    // Comparing signs rather than multiplying, products overflow sooner on integers
+	    let opposite = |u: T, v: T| (u < zero && v > zero) || (u > zero && v < zero);
+	    if opposite(d1, d2) && opposite(d3, d4) {
+	        return true;
+	    }
+	    // Touching or overlapping
+	    (d1 == zero && on_segment(c, d, a)) || (d2 == zero && on_segment(c, d, b)) ||
+	    (d3 == zero && on_segment(a, b, c)) || (d4 == zero && on_segment(a, b, d))
+	}
+
+	// A polygon is simple when no two edges meet, apart from consecutive edges at their shared vertex
+	fn is_simple<T: Coordinate>(points: &[Point<T>]) -> bool {
+	    let n = points.len();
+	    for i in range(0, n) {
+	        for j in range(i + 1, n) {
+	            if j == i + 1 || (i == 0 && j == n - 1) {
+	                continue;
+	            }
+	            if segments_intersect(points[i], points[(i + 1) % n], points[j], points[(j + 1) % n]) {
+	                return false;
+	            }
+	        }
+	    }
+	    true
+	}
+
+	let square = vec![Point { x: 0.0, y: 0.0 }, Point { x: 0.0, y: 2.0 },
+	                  Point { x: 2.0, y: 2.0 }, Point { x: 2.0, y: 0.0 }];
+	assert!(is_simple(square.as_slice()));
+	let square = counter_clockwise(square); // it was given clockwise
+	println!("area: {}", area(Polygon(square)));
+
+	let bow_tie = vec![Point { x: 0.0, y: 0.0 }, Point { x: 2.0, y: 2.0 },
+	                   Point { x: 2.0, y: 0.0 }, Point { x: 0.0, y: 2.0 }];
+	assert!(!is_simple(bow_tie.as_slice()));
+
+	let ellipse = Ellipse(Point { x: 0.0, y: 0.0 }, 3.0, 1.0);
+	println!("area: {}", area(ellipse));
+	let triangle = Triangle(Point { x: 0.0, y: 0.0 }, Point { x: 4.0, y: 0.0 }, Point { x: 0.0, y: 3.0 });
+	println!("area: {}", area(triangle));
+
+	/* Perimeter, centroid and second moment of area follow the same pattern, on f64 coordinates
+	 * since none of them is exact anyway. They borrow the shape rather than taking it, since
+	 * they are often asked about the same polygon in a row.
+	 */
+	fn perimeter(sh: &Shape<f64>) -> f64 {
+	    match *sh {
+	        Circle(_, size) => 2.0 * f64::consts::PI * size,
+	        Rectangle(p1, p2) => 2.0 * ((p2.x - p1.x).abs() + (p2.y - p1.y).abs()),
+	        Polygon(ref points) => outline_length(points.as_slice()),
+	        // Ramanujan's approximation, there is no closed form for the ellipse
+	        Ellipse(_, rx, ry) => f64::consts::PI * (3.0 * (rx + ry) - ((3.0 * rx + ry) * (rx + 3.0 * ry)).sqrt()),
+	        Triangle(a, b, c) => outline_length([a, b, c])
+	    }
+	}
+
+	fn outline_length(points: &[Point<f64>]) -> f64 {
+	    let n = points.len();
+	    let mut length = 0.0;
+	    for i in range(0, n) {
+	        let (p, q) = (points[i], points[(i + 1) % n]);
+	        length += ((q.x - p.x) * (q.x - p.x) + (q.y - p.y) * (q.y - p.y)).sqrt();
+	    }
+	    length
+	}
+
+	fn centroid(sh: &Shape<f64>) -> Point<f64> {
+	    match *sh {
+	        Circle(center, _) | Ellipse(center, _, _) => center,
+	        Rectangle(p1, p2) => Point { x: (p1.x + p2.x) / 2.0, y: (p1.y + p2.y) / 2.0 },
+	        Polygon(ref points) => polygon_moments(points.as_slice()).val0(),
+	        Triangle(a, b, c) => Point { x: (a.x + b.x + c.x) / 3.0, y: (a.y + b.y + c.y) / 3.0 }
+	    }
+	}
+
+	// Second moment of area about the horizontal (ix) and vertical (iy) axes through the centroid
+	struct SecondMoment {
+	    ix: f64,
+	    iy: f64,
+	    ixy: f64
+	}
+
+	fn second_moment(sh: &Shape<f64>) -> SecondMoment {
+	    let pi = f64::consts::PI;
+	    match *sh {
+	        Circle(_, r) => SecondMoment { ix: pi * r * r * r * r / 4.0, iy: pi * r * r * r * r / 4.0, ixy: 0.0 },
+	        Ellipse(_, rx, ry) => SecondMoment { ix: pi * rx * ry * ry * ry / 4.0, iy: pi * rx * rx * rx * ry / 4.0, ixy: 0.0 },
+	        Rectangle(p1, p2) => {
+	            let (w, h) = ((p2.x - p1.x).abs(), (p2.y - p1.y).abs());
+	            SecondMoment { ix: w * h * h * h / 12.0, iy: h * w * w * w / 12.0, ixy: 0.0 }
+	        },
+	        Polygon(ref points) => polygon_moments(points.as_slice()).val1(),
+	        Triangle(a, b, c) => polygon_moments([a, b, c]).val1()
+	    }
+	}
+
+	/* Centroid and second moment of a simple polygon, in either winding order. The sums give the
+	 * moments about the origin, the parallel axis theorem then moves them to the centroid.
+	 */
+	fn polygon_moments(points: &[Point<f64>]) -> (Point<f64>, SecondMoment) {
+	    let n = points.len();
+	    let (mut a, mut cx, mut cy) = (0.0, 0.0, 0.0);
+	    let (mut ix, mut iy, mut ixy) = (0.0, 0.0, 0.0);
+	    for i in range(0, n) {
+	        let (p, q) = (points[i], points[(i + 1) % n]);
+	        let cross = p.x * q.y - q.x * p.y;
+	        a += cross / 2.0;
+	        cx += (p.x + q.x) * cross / 6.0;
+	        cy += (p.y + q.y) * cross / 6.0;
+	        ix += (p.y * p.y + p.y * q.y + q.y * q.y) * cross / 12.0;
+	        iy += (p.x * p.x + p.x * q.x + q.x * q.x) * cross / 12.0;
+	        ixy += (p.x * q.y + 2.0 * p.x * p.y + 2.0 * q.x * q.y + q.x * p.y) * cross / 24.0;
+	    }
+	    if a == 0.0 {
+	        // Degenerate polygon, all of its vertices are aligned
+	        let (sx, sy) = points.iter().fold((0.0, 0.0), |(sx, sy), p| (sx + p.x, sy + p.y));
+	        let center = Point { x: sx / n as f64, y: sy / n as f64 };
+	        return (center, SecondMoment { ix: 0.0, iy: 0.0, ixy: 0.0 });
+	    }
+	    let center = Point { x: cx / a, y: cy / a };
+	    // Clockwise polygons give negative sums, the signs cancel out in the divisions above but not here
+	    let sign = if a < 0.0 { -1.0 } else { 1.0 };
+	    (center, SecondMoment {
+	        ix: sign * (ix - a * center.y * center.y),
+	        iy: sign * (iy - a * center.x * center.x),
+	        ixy: sign * (ixy - a * center.x * center.y)
+	    })
+	}
+
+	let beam = Rectangle(Point { x: 0.0, y: 0.0 }, Point { x: 0.1, y: 0.3 });
+	let moment = second_moment(&beam);
+	println!("perimeter: {}, ix: {}, iy: {}", perimeter(&beam), moment.ix, moment.iy);
+
+	fn point_from_direction(dir: Direction) -> Point<f64> {
 	    match dir {
 	        North => Point { x:  0.0, y:  1.0 },
 	        East  => Point { x:  1.0, y:  0.0 },